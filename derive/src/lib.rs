@@ -0,0 +1,230 @@
+//! Derive macro for [uniquote].
+//!
+//! This crate should not be used directly. Its macro is re-exported by
+//! uniquote when the **derive** feature is enabled, and it is documented
+//! there.
+//!
+//! [uniquote]: https://crates.io/crates/uniquote
+
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::TokenTree;
+use quote::format_ident;
+use quote::quote;
+use quote::ToTokens;
+use syn::parse_macro_input;
+use syn::parse_quote;
+use syn::Attribute;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Fields;
+use syn::Ident;
+use syn::LitStr;
+use syn::Result;
+use syn::Type;
+
+fn parse_separator(
+    attrs: &[Attribute],
+    inherited: Option<&LitStr>,
+) -> Result<Option<LitStr>> {
+    let mut separator = None;
+    for attr in attrs.iter().filter(|x| x.path().is_ident("quote")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("separator") {
+                return Err(meta.error("unsupported attribute"));
+            }
+            if separator.is_some() {
+                return Err(meta.error("duplicate separator"));
+            }
+            separator = Some(meta.value()?.parse()?);
+            Ok(())
+        })?;
+    }
+    Ok(separator.or_else(|| inherited.cloned()))
+}
+
+fn reject_attrs(attrs: &[Attribute]) -> Result<()> {
+    if let Some(attr) = attrs.iter().find(|x| x.path().is_ident("quote")) {
+        return Err(Error::new_spanned(
+            attr,
+            "attributes are not supported on fields",
+        ));
+    }
+    Ok(())
+}
+
+fn contains_param(tokens: TokenStream2, params: &HashSet<Ident>) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Group(group) => contains_param(group.stream(), params),
+        TokenTree::Ident(ident) => params.contains(&ident),
+        _ => false,
+    })
+}
+
+// Returns the referenced type and the number of references removed, since
+// references do not implement the trait.
+fn strip_references(mut ty: &Type) -> (&Type, usize) {
+    let mut count = 0;
+    while let Type::Reference(reference) = ty {
+        ty = &reference.elem;
+        count += 1;
+    }
+    (ty, count)
+}
+
+// Escapes each field, with separators between them.
+fn escape_fields(
+    fields: &Fields,
+    bindings: &[TokenStream2],
+    separator: Option<&LitStr>,
+    span: &dyn ToTokens,
+) -> Result<TokenStream2> {
+    if fields.len() > 1 && separator.is_none() {
+        return Err(Error::new_spanned(
+            span,
+            "a separator is required for multiple fields, such as \
+             #[quote(separator = \",\")]",
+        ));
+    }
+    for field in fields {
+        reject_attrs(&field.attrs)?;
+    }
+
+    let separator =
+        separator.map(|x| quote!(::uniquote::Quote::escape(#x, f)?;));
+    let escapes = fields.iter().zip(bindings).map(|(field, binding)| {
+        let (ty, count) = strip_references(&field.ty);
+        let derefs = (0..count).map(|_| quote!(*));
+        quote!(<#ty as ::uniquote::Quote>::escape(#(#derefs)* #binding, f)?;)
+    });
+    let mut result = TokenStream2::new();
+    for (i, escape) in escapes.enumerate() {
+        if i != 0 {
+            result.extend(separator.clone());
+        }
+        result.extend(escape);
+    }
+    Ok(result)
+}
+
+fn bindings(fields: &Fields) -> Vec<TokenStream2> {
+    (0..fields.len())
+        .map(|i| format_ident!("__uniquote_field_{}", i).into_token_stream())
+        .collect()
+}
+
+fn pattern(fields: &Fields, bindings: &[TokenStream2]) -> TokenStream2 {
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|x| &x.ident);
+            quote!({ #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!((#(#bindings),*)),
+        Fields::Unit => TokenStream2::new(),
+    }
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream2> {
+    let separator = parse_separator(&input.attrs, None)?;
+
+    let mut field_types: Vec<&Type> = Vec::new();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            field_types.extend(data.fields.iter().map(|x| &x.ty));
+            let bindings = bindings(&data.fields);
+            let escapes = escape_fields(
+                &data.fields,
+                &bindings,
+                separator.as_ref(),
+                &input.ident,
+            )?;
+            let pattern = pattern(&data.fields, &bindings);
+            quote! {
+                let Self #pattern = self;
+                #escapes
+                ::core::result::Result::Ok(())
+            }
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let separator =
+                    parse_separator(&variant.attrs, separator.as_ref())?;
+                field_types.extend(variant.fields.iter().map(|x| &x.ty));
+                let bindings = bindings(&variant.fields);
+                let escapes = escape_fields(
+                    &variant.fields,
+                    &bindings,
+                    separator.as_ref(),
+                    &variant.ident,
+                )?;
+                let name = &variant.ident;
+                let pattern = pattern(&variant.fields, &bindings);
+                arms.push(quote!(Self::#name #pattern => { #escapes }));
+            }
+            if arms.is_empty() {
+                quote!(match *self {})
+            } else {
+                quote! {
+                    match self { #(#arms)* }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "unions are not supported",
+            ));
+        }
+    };
+
+    // Unit types do not use the formatter.
+    let unused = field_types.is_empty().then(|| quote!(let _ = f;));
+
+    let params: HashSet<_> = input
+        .generics
+        .type_params()
+        .map(|x| x.ident.clone())
+        .collect();
+    let bounds: Vec<Type> = field_types
+        .into_iter()
+        .map(|x| strip_references(x).0)
+        .filter(|x| contains_param(x.to_token_stream(), &params))
+        .cloned()
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for ty in bounds {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::uniquote::Quote));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::uniquote::Quote for #name #ty_generics
+        #where_clause
+        {
+            fn escape(
+                &self,
+                f: &mut ::uniquote::Formatter<'_>,
+            ) -> ::uniquote::Result {
+                #unused
+                #body
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(Quote, attributes(quote))]
+pub fn derive_quote(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
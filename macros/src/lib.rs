@@ -0,0 +1,432 @@
+//! Procedural macros for [uniquote].
+//!
+//! This crate should not be used directly. Its macros are re-exported by
+//! uniquote when the **macros** feature is enabled, and they are documented
+//! there.
+//!
+//! [uniquote]: https://crates.io/crates/uniquote
+
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::format_ident;
+use quote::quote;
+use syn::parse::Parse;
+use syn::parse::ParseStream;
+use syn::parse::Parser as _;
+use syn::Error;
+use syn::Expr;
+use syn::Ident;
+use syn::LitStr;
+use syn::Result;
+use syn::Token;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Kind {
+    Eprint,
+    Eprintln,
+    Format,
+    Print,
+    Println,
+    Write,
+    Writeln,
+}
+
+impl Kind {
+    fn has_destination(self) -> bool {
+        matches!(self, Self::Write | Self::Writeln)
+    }
+
+    fn path(self) -> TokenStream2 {
+        match self {
+            Self::Eprint => quote!(::std::eprint),
+            Self::Eprintln => quote!(::std::eprintln),
+            Self::Format => quote!(::std::format),
+            Self::Print => quote!(::std::print),
+            Self::Println => quote!(::std::println),
+            Self::Write => quote!(::core::write),
+            Self::Writeln => quote!(::core::writeln),
+        }
+    }
+}
+
+struct Arg {
+    name: Option<Ident>,
+    expr: Expr,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let name = if input.peek(Ident)
+            && input.peek2(Token![=])
+            && !input.peek2(Token![==])
+        {
+            let name = input.parse()?;
+            let _: Token![=] = input.parse()?;
+            Some(name)
+        } else {
+            None
+        };
+        Ok(Self {
+            name,
+            expr: input.parse()?,
+        })
+    }
+}
+
+struct Input {
+    destination: Option<Expr>,
+    format: Option<LitStr>,
+    args: Vec<Arg>,
+}
+
+impl Input {
+    fn parse(input: ParseStream<'_>, kind: Kind) -> Result<Self> {
+        let destination = if kind.has_destination() {
+            let destination = input.parse()?;
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+            Some(destination)
+        } else {
+            None
+        };
+
+        let mut format = None;
+        let mut args = Vec::new();
+        if !input.is_empty() {
+            format = Some(input.parse().map_err(|error| {
+                Error::new(
+                    error.span(),
+                    "format argument must be a string literal",
+                )
+            })?);
+            while !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+                if input.is_empty() {
+                    break;
+                }
+                args.push(input.parse()?);
+            }
+        }
+
+        Ok(Self {
+            destination,
+            format,
+            args,
+        })
+    }
+}
+
+// An argument referenced by a placeholder.
+enum Target {
+    Arg(usize),
+    Capture(Ident),
+}
+
+enum Piece {
+    Text(String),
+    Quoted(Target),
+}
+
+#[derive(Clone, Copy, Default)]
+struct Usage {
+    plain: bool,
+    quoted: bool,
+    // Counts must be passed by value.
+    count: bool,
+}
+
+struct Parser<'a> {
+    names: HashMap<String, usize>,
+    usages: Vec<Usage>,
+    next_index: usize,
+    lit: &'a LitStr,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::new(self.lit.span(), message)
+    }
+
+    fn next_index(&mut self) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        index
+    }
+
+    fn resolve(&mut self, reference: &str) -> Result<Target> {
+        let index = if reference.is_empty() {
+            self.next_index()
+        } else if let Ok(index) = reference.parse() {
+            index
+        } else if let Some(&index) = self.names.get(reference) {
+            index
+        } else {
+            let capture = syn::parse_str::<Ident>(reference)
+                .map_err(|_| self.error("invalid argument name"))?;
+            return Ok(Target::Capture(capture));
+        };
+        if index >= self.usages.len() {
+            return Err(self.error(&format!(
+                "invalid reference to positional argument {}",
+                index,
+            )));
+        }
+        Ok(Target::Arg(index))
+    }
+
+    fn mark_count(&mut self, reference: &str) {
+        let index = reference
+            .parse()
+            .ok()
+            .or_else(|| self.names.get(reference).copied());
+        if let Some(usage) = index.and_then(|x| self.usages.get_mut(x)) {
+            usage.plain = true;
+            usage.count = true;
+        }
+    }
+
+    // Returns the format spec with implicit precision replaced by an
+    // explicit argument, so that implicit indices can be rewritten.
+    fn rewrite_spec(&mut self, spec: &str) -> String {
+        let mut spec = spec.to_owned();
+        if let Some(start) = spec.find(".*") {
+            let index = self.next_index();
+            spec.replace_range(start..start + 2, &format!(".{}$", index));
+        }
+
+        let mut end = 0;
+        while let Some(offset) = spec[end..].find('$') {
+            let position = end + offset;
+            let start = spec[..position]
+                .rfind(|x: char| !x.is_alphanumeric() && x != '_')
+                .map_or(0, |x| x + 1);
+            let reference = spec[start..position].to_owned();
+            if !reference.is_empty() {
+                self.mark_count(&reference);
+            }
+            end = position + 1;
+        }
+        spec
+    }
+
+    fn parse_placeholder(&mut self, placeholder: &str) -> Result<Piece> {
+        let (reference, spec) = placeholder
+            .split_once(':')
+            .map_or((placeholder, None), |(x, y)| (x, Some(y)));
+        let reference = reference.trim();
+
+        if spec == Some("q") {
+            let target = self.resolve(reference)?;
+            if let Target::Arg(index) = target {
+                self.usages[index].quoted = true;
+            }
+            return Ok(Piece::Quoted(target));
+        }
+
+        let spec = spec.map(|x| self.rewrite_spec(x));
+        let target = self.resolve(reference)?;
+        let reference = match target {
+            Target::Arg(index) => {
+                self.usages[index].plain = true;
+                if reference.is_empty() {
+                    index.to_string()
+                } else {
+                    reference.to_owned()
+                }
+            }
+            Target::Capture(_) => reference.to_owned(),
+        };
+        Ok(Piece::Text(if let Some(spec) = spec {
+            format!("{{{}:{}}}", reference, spec)
+        } else {
+            format!("{{{}}}", reference)
+        }))
+    }
+
+    fn parse(&mut self) -> Result<Vec<Piece>> {
+        let string = self.lit.value();
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = string.char_indices().peekable();
+        while let Some((i, ch)) = chars.next() {
+            match ch {
+                '{' if chars.next_if(|&(_, x)| x == '{').is_some() => {
+                    text.push_str("{{");
+                }
+                '}' if chars.next_if(|&(_, x)| x == '}').is_some() => {
+                    text.push_str("}}");
+                }
+                '{' => {
+                    let Some(length) = string[i + 1..].find('}') else {
+                        return Err(self.error("unterminated placeholder"));
+                    };
+                    let placeholder = &string[i + 1..i + 1 + length];
+                    while chars
+                        .next_if(|&(x, _)| x <= i + 1 + length)
+                        .is_some()
+                    {}
+                    match self.parse_placeholder(placeholder)? {
+                        Piece::Text(placeholder) => {
+                            text.push_str(&placeholder)
+                        }
+                        piece => {
+                            pieces.push(Piece::Text(text));
+                            text = String::new();
+                            pieces.push(piece);
+                        }
+                    }
+                }
+                '}' => {
+                    return Err(self.error("unmatched `}` in format string"))
+                }
+                _ => text.push(ch),
+            }
+        }
+        pieces.push(Piece::Text(text));
+        Ok(pieces)
+    }
+}
+
+fn expand(input: Input, kind: Kind) -> Result<TokenStream2> {
+    let path = kind.path();
+    let destination = input.destination.map(|x| quote!(#x,));
+    let Some(lit) = input.format else {
+        return Ok(quote!(#path!(#destination)));
+    };
+
+    let mut names = HashMap::new();
+    for (i, arg) in input.args.iter().enumerate() {
+        if let Some(name) = &arg.name {
+            let _ = names.insert(name.to_string(), i);
+        }
+    }
+    let mut parser = Parser {
+        names,
+        usages: vec![Usage::default(); input.args.len()],
+        next_index: 0,
+        lit: &lit,
+    };
+    let pieces = parser.parse()?;
+    let usages = parser.usages;
+
+    let bindings: Vec<_> = (0..input.args.len())
+        .map(|i| format_ident!("__uniquote_arg_{}", i))
+        .collect();
+
+    let mut values = Vec::new();
+    let mut format_args = Vec::new();
+    for ((arg, usage), binding) in
+        input.args.iter().zip(&usages).zip(&bindings)
+    {
+        let expr = &arg.expr;
+        values.push(if usage.count {
+            quote!(#expr)
+        } else {
+            quote!(&(#expr))
+        });
+
+        let value = if usage.quoted && !usage.plain {
+            quote!(#binding.quote())
+        } else {
+            quote!(#binding)
+        };
+        format_args.push(if let Some(name) = &arg.name {
+            quote!(#name = #value)
+        } else {
+            value
+        });
+    }
+
+    let mut string = String::new();
+    let mut extra_args = Vec::new();
+    let mut quoted = false;
+    // Whether a quoted version of each argument has been added.
+    let mut quoted_args = vec![false; input.args.len()];
+    for piece in pieces {
+        let target = match piece {
+            Piece::Text(text) => {
+                string.push_str(&text);
+                continue;
+            }
+            Piece::Quoted(target) => target,
+        };
+        quoted = true;
+
+        let reference = match target {
+            Target::Arg(index) => {
+                if usages[index].plain {
+                    let name = format_ident!("__uniquote_quoted_{}", index);
+                    if !quoted_args[index] {
+                        quoted_args[index] = true;
+                        let binding = &bindings[index];
+                        extra_args.push(quote!(#name = #binding.quote()));
+                    }
+                    name.to_string()
+                } else if let Some(name) = &input.args[index].name {
+                    name.to_string()
+                } else {
+                    index.to_string()
+                }
+            }
+            Target::Capture(mut capture) => {
+                let name =
+                    format_ident!("__uniquote_capture_{}", extra_args.len());
+                capture.set_span(lit.span());
+                extra_args.push(quote!(#name = #capture.quote()));
+                name.to_string()
+            }
+        };
+        string.push('{');
+        string.push_str(&reference);
+        string.push('}');
+    }
+    let lit = LitStr::new(&string, lit.span());
+
+    let import = quoted.then(|| {
+        quote!(
+            use ::uniquote::Quote as _;
+        )
+    });
+    Ok(quote! {
+        match (#(#values,)*) {
+            (#(#bindings,)*) => {
+                #import
+                #path!(#destination #lit, #(#format_args,)* #(#extra_args,)*)
+            }
+        }
+    })
+}
+
+fn expand_kind(input: TokenStream, kind: Kind) -> TokenStream {
+    let parser = |input: ParseStream<'_>| Input::parse(input, kind);
+    let input = match parser.parse(input) {
+        Ok(input) => input,
+        Err(error) => return error.into_compile_error().into(),
+    };
+    expand(input, kind)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+macro_rules! r#macro {
+    ( $($name:ident => $kind:ident ,)+ ) => {
+    $(
+        #[proc_macro]
+        pub fn $name(input: TokenStream) -> TokenStream {
+            expand_kind(input, Kind::$kind)
+        }
+    )+
+    };
+}
+r#macro!(
+    qeprint => Eprint,
+    qeprintln => Eprintln,
+    qformat => Format,
+    qprint => Print,
+    qprintln => Println,
+    qwrite => Write,
+    qwriteln => Writeln,
+);
@@ -0,0 +1,136 @@
+use std::ffi::OsStr;
+use std::ffi::OsString;
+
+use super::unquote;
+use super::ParseError;
+
+/// Parses a command line argument that may be quoted.
+///
+/// If the argument begins and ends with a quote, it is decoded using
+/// [`unquote`], and an error is returned if decoding fails. Otherwise, it is
+/// returned unchanged. This allows users to pass values that were displayed
+/// by [`Quote::quote`], such as names of files that cannot be typed. An
+/// argument containing surrounding quotes can still be passed by quoting it
+/// again.
+///
+/// # Errors
+///
+/// Returns an error if the argument is quoted but cannot be decoded, or if
+/// the decoded bytes cannot be represented on this platform.
+///
+/// # Examples
+///
+/// ```
+/// use std::ffi::OsStr;
+///
+/// # #[cfg(unix)]
+/// # {
+/// # use std::os::unix::ffi::OsStrExt;
+/// #
+/// assert_eq!(
+///     Ok(OsStr::from_bytes(b"data\xFF.bin")),
+///     uniquote::parse_quoted_arg(OsStr::new(r#""data{~xff}.bin""#))
+///         .as_deref(),
+/// );
+/// # }
+/// assert_eq!(
+///     Ok(OsStr::new("data.bin")),
+///     uniquote::parse_quoted_arg(OsStr::new("data.bin")).as_deref(),
+/// );
+/// ```
+///
+/// [`Quote::quote`]: super::Quote::quote
+/// [`unquote`]: super::unquote()
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+pub fn parse_quoted_arg(arg: &OsStr) -> Result<OsString, ParseError> {
+    match arg.to_str() {
+        Some(string)
+            if string.len() >= 2
+                && string.starts_with('"')
+                && string.ends_with('"') =>
+        {
+            unquote::unquote_os_string(string)
+        }
+        _ => Ok(arg.to_owned()),
+    }
+}
+
+#[cfg(feature = "clap")]
+mod clap {
+    use std::ffi::OsStr;
+    use std::ffi::OsString;
+
+    use clap::builder::TypedValueParser;
+    use clap::error::ErrorKind;
+    use clap::Arg;
+    use clap::Command;
+    use clap::Error;
+
+    use crate::Quote;
+
+    /// A [`clap`] value parser for arguments that may be quoted.
+    ///
+    /// Arguments are parsed using [`parse_quoted_arg`], and the parsed value
+    /// is an [`OsString`]. Other types can be produced using
+    /// [`TypedValueParser::map`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use clap::builder::TypedValueParser;
+    /// use clap::Arg;
+    /// use clap::Command;
+    /// use uniquote::QuotedArgParser;
+    ///
+    /// let command = Command::new("app").arg(
+    ///     Arg::new("file")
+    ///         .value_parser(QuotedArgParser::new().map(PathBuf::from)),
+    /// );
+    /// let matches = command.get_matches_from(["app", r#""new{~n}file""#]);
+    /// assert_eq!(
+    ///     Some(&PathBuf::from("new\nfile")),
+    ///     matches.get_one("file"),
+    /// );
+    /// ```
+    ///
+    /// [`parse_quoted_arg`]: super::parse_quoted_arg
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "clap")))]
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct QuotedArgParser(());
+
+    impl QuotedArgParser {
+        /// Creates a new parser.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self(())
+        }
+    }
+
+    impl TypedValueParser for QuotedArgParser {
+        type Value = OsString;
+
+        #[inline]
+        fn parse_ref(
+            &self,
+            cmd: &Command,
+            arg: Option<&Arg>,
+            value: &OsStr,
+        ) -> Result<Self::Value, Error> {
+            super::parse_quoted_arg(value).map_err(|error| {
+                let arg = arg.map_or_else(|| "...".into(), Arg::to_string);
+                let message = format!(
+                    "invalid value {} for '{}': {}\n",
+                    value.quote(),
+                    arg,
+                    error,
+                );
+                Error::raw(ErrorKind::ValueValidation, message).with_cmd(cmd)
+            })
+        }
+    }
+}
+#[cfg(feature = "clap")]
+pub use self::clap::QuotedArgParser;
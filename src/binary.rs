@@ -0,0 +1,236 @@
+use core::fmt;
+use core::fmt::Write;
+
+use super::formatter::Options;
+use super::formatter::SegmentWriter;
+use super::unquote::Decoder;
+use super::EscapeKind;
+use super::Formatter;
+use super::Quote;
+use super::Segment;
+
+const BYTES_PER_LINE: usize = 16;
+
+// https://datatracker.ietf.org/doc/html/draft-eastlake-fnv
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Representation {
+    HexDump,
+    Summary,
+}
+
+/// A compact representation used for strings that appear to be binary data.
+///
+/// While a string is quoted, the number of escaped characters is compared to
+/// the number of characters output literally. When the percentage of escaped
+/// characters exceeds the [`threshold`], the string is not quoted. The
+/// selected representation is displayed instead, beginning with a header
+/// such as `<binary data: 20 bytes, hex dump>`. Since quoted strings always
+/// begin with a quote, this output cannot be mistaken for one.
+///
+/// The bytes displayed are those that would be decoded from the quoted
+/// string by [`unquote`]. If they cannot be determined, such as when a
+/// surrogate code point was escaped, the string is quoted as usual.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Binary;
+/// use uniquote::Quote;
+///
+/// let bytes = b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR\0\0\x01\0";
+/// assert_eq!(
+///     "<binary data: 20 bytes, hex dump>
+/// 00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|
+/// 00000010  00 00 01 00                                       |....|",
+///     bytes.quote().binary(Binary::hex_dump().min_len(0)).to_string(),
+/// );
+/// assert_eq!(
+///     r#""IHDR""#,
+///     b"IHDR".quote().binary(Binary::hex_dump().min_len(0)).to_string(),
+/// );
+/// ```
+///
+/// [`threshold`]: Self::threshold
+/// [`unquote`]: super::unquote()
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Binary {
+    representation: Representation,
+    threshold: u8,
+    min_len: usize,
+}
+
+impl Binary {
+    const fn new(representation: Representation) -> Self {
+        Self {
+            representation,
+            threshold: 30,
+            min_len: 32,
+        }
+    }
+
+    /// Displays binary data as a hex dump.
+    ///
+    /// Each line contains the offset of the first byte, up to 16 bytes in
+    /// hexadecimal, and the same bytes as ASCII characters. Unprintable bytes
+    /// are displayed as periods in the last column. Each line begins with
+    /// any indentation and prefix configured for the output.
+    #[inline]
+    #[must_use]
+    pub const fn hex_dump() -> Self {
+        Self::new(Representation::HexDump)
+    }
+
+    /// Displays only the length and a hash of binary data.
+    ///
+    /// The hash is the 64-bit FNV-1a hash of the bytes. It is not
+    /// cryptographically secure, but it can be used to distinguish values in
+    /// diagnostic output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Binary;
+    /// use uniquote::Quote;
+    ///
+    /// assert_eq!(
+    ///     "<binary data: 64 bytes, FNV-1a hash 0xb9b23f3a46fd0825>",
+    ///     [0; 64].quote().binary(Binary::summary()).to_string(),
+    /// );
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn summary() -> Self {
+        Self::new(Representation::Summary)
+    }
+
+    /// Sets the percentage of escaped characters above which a string is
+    /// considered to be binary data.
+    ///
+    /// The default threshold is 30 percent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the percentage is greater than 100.
+    #[inline]
+    #[must_use]
+    pub const fn threshold(mut self, percent: u8) -> Self {
+        assert!(percent <= 100, "threshold is greater than 100 percent");
+        self.threshold = percent;
+        self
+    }
+
+    /// Sets the minimum number of characters for a string to be considered
+    /// binary data.
+    ///
+    /// Shorter strings are always quoted, since they are readable regardless
+    /// of how many characters are escaped. The default length is 32.
+    #[inline]
+    #[must_use]
+    pub const fn min_len(mut self, len: usize) -> Self {
+        self.min_len = len;
+        self
+    }
+
+    // Returns [false] if the value should be quoted instead.
+    pub(super) fn write<T>(
+        self,
+        value: &T,
+        f: &mut Formatter<'_>,
+    ) -> Result<bool, fmt::Error>
+    where
+        T: Quote + ?Sized,
+    {
+        let mut stats = Stats {
+            escaped: 0,
+            literal: 0,
+            decoder: Decoder::new(),
+        };
+        value
+            .escape(&mut Formatter::with_segments(&mut stats, Options::new()))
+            .map_err(|x| x.0)?;
+        let len = stats.escaped + stats.literal;
+        if len < self.min_len
+            || stats.escaped * 100 <= usize::from(self.threshold) * len
+        {
+            return Ok(false);
+        }
+
+        let Some(bytes) = stats.decoder.finish() else {
+            return Ok(false);
+        };
+
+        write!(f.writer, "<binary data: {} bytes, ", bytes.len())?;
+        match self.representation {
+            Representation::HexDump => {
+                f.writer.write_str("hex dump>")?;
+                for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+                    f.write_line_break()?;
+                    write_hex_line(i * BYTES_PER_LINE, line, &mut f.writer)?;
+                }
+            }
+            Representation::Summary => {
+                let hash = bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &x| {
+                    (hash ^ u64::from(x)).wrapping_mul(FNV_PRIME)
+                });
+                write!(f.writer, "FNV-1a hash {:#018x}>", hash)?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn write_hex_line(
+    offset: usize,
+    line: &[u8],
+    f: &mut dyn fmt::Write,
+) -> fmt::Result {
+    write!(f, "{:08x} ", offset)?;
+    for i in 0..BYTES_PER_LINE {
+        if i % 8 == 0 {
+            f.write_char(' ')?;
+        }
+        match line.get(i) {
+            Some(byte) => write!(f, "{:02x} ", byte)?,
+            None => f.write_str("   ")?,
+        }
+    }
+
+    f.write_str(" |")?;
+    for &byte in line {
+        let ch = if byte.is_ascii_graphic() || byte == b' ' {
+            byte.into()
+        } else {
+            '.'
+        };
+        f.write_char(ch)?;
+    }
+    f.write_char('|')
+}
+
+// Counts the characters of the original string that were escaped, while
+// decoding its bytes. Escaped braces and quotes are counted as literal
+// characters, since they are readable.
+struct Stats {
+    escaped: usize,
+    literal: usize,
+    decoder: Decoder,
+}
+
+impl SegmentWriter for Stats {
+    fn write_segment(&mut self, segment: Segment<'_>) -> fmt::Result {
+        match segment {
+            Segment::Literal(string) => {
+                self.literal += string.chars().count();
+            }
+            Segment::Escape(EscapeKind::Brace(_) | EscapeKind::Quote) => {
+                self.literal += 1;
+            }
+            Segment::Escape(_) => self.escaped += 1,
+        }
+        self.decoder.write_segment(segment)
+    }
+}
@@ -0,0 +1,154 @@
+use core::fmt;
+
+const RESET: &str = "\x1B[0m";
+
+const fn validate(style: &str) {
+    let style = style.as_bytes();
+    let mut i = 0;
+    while i < style.len() {
+        assert!(
+            matches!(style[i], b'0'..=b'9' | b';'),
+            "invalid SGR parameters",
+        );
+        i += 1;
+    }
+}
+
+/// ANSI styles applied to quoted strings.
+///
+/// Styles are given as SGR parameters, which are the part of an ANSI escape
+/// sequence between `ESC [` and `m`. For example, `"1;31"` selects bold red
+/// text. An empty string disables styling for that part of the output.
+///
+/// Only the sequences created from these parameters are written to the
+/// output. All control characters in quoted strings, including `ESC`, are
+/// still escaped.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Colors;
+/// use uniquote::Quote;
+///
+/// let colors = Colors::new().escape("33");
+/// assert_eq!(
+///     "\"foo\x1B[33m{~n}\x1B[0mbar\"",
+///     "foo\nbar".quote().colors(colors).to_string(),
+/// );
+/// ```
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "color")))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Colors {
+    escape: &'static str,
+    quote: &'static str,
+}
+
+impl Colors {
+    /// Styles that do not modify the output.
+    ///
+    /// Using this value is equivalent to not setting any colors.
+    pub const NONE: Self = Self {
+        escape: "",
+        quote: "",
+    };
+
+    /// Creates the default styles.
+    ///
+    /// Escapes are displayed in bold red, and quotes are not styled.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            escape: "1;31",
+            quote: "",
+        }
+    }
+
+    /// Sets the style used for escapes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters contain characters other than ASCII digits
+    /// and semicolons.
+    #[inline]
+    #[must_use]
+    pub const fn escape(mut self, style: &'static str) -> Self {
+        validate(style);
+        self.escape = style;
+        self
+    }
+
+    /// Sets the style used for the surrounding quotes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters contain characters other than ASCII digits
+    /// and semicolons.
+    #[inline]
+    #[must_use]
+    pub const fn quote(mut self, style: &'static str) -> Self {
+        validate(style);
+        self.quote = style;
+        self
+    }
+
+    pub(super) fn write_escape<F>(
+        &self,
+        f: &mut dyn fmt::Write,
+        write_fn: F,
+    ) -> fmt::Result
+    where
+        F: FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+    {
+        write(self.escape, f, write_fn)
+    }
+
+    pub(super) fn start_escape(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        start(self.escape, f)
+    }
+
+    pub(super) fn end_escape(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        end(self.escape, f)
+    }
+
+    pub(super) fn write_quote<F>(
+        &self,
+        f: &mut dyn fmt::Write,
+        write_fn: F,
+    ) -> fmt::Result
+    where
+        F: FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+    {
+        write(self.quote, f, write_fn)
+    }
+}
+
+impl Default for Colors {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn start(style: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    if style.is_empty() {
+        return Ok(());
+    }
+    write!(f, "\x1B[{}m", style)
+}
+
+fn end(style: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    if style.is_empty() {
+        return Ok(());
+    }
+    f.write_str(RESET)
+}
+
+fn write<F>(style: &str, f: &mut dyn fmt::Write, write_fn: F) -> fmt::Result
+where
+    F: FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+{
+    start(style, f)?;
+    write_fn(f)?;
+    end(style, f)
+}
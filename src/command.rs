@@ -0,0 +1,306 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::process::Command;
+
+use super::Quote;
+use super::QuotingStyle;
+
+fn write_value<T>(
+    quoting_style: Option<QuotingStyle>,
+    value: &T,
+    f: &mut Formatter<'_>,
+) -> fmt::Result
+where
+    T: AsRef<OsStr> + ?Sized,
+{
+    let value = value.as_ref().quote();
+    if let Some(style) = quoting_style {
+        value.quoting_style(style).fmt(f)
+    } else {
+        value.fmt(f)
+    }
+}
+
+/// Displays a sequence of arguments as a command line.
+///
+/// Each argument is quoted separately, and arguments are separated by
+/// spaces. Since every argument is enclosed in quotes by default, spaces
+/// within arguments cannot be confused with separators.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+///
+/// use uniquote::QuotedArgs;
+///
+/// let args: Vec<_> = env::args_os().collect();
+/// println!("arguments: {}", QuotedArgs::new(&args));
+///
+/// assert_eq!(
+///     r#""cp" "a b" "c{~t}d""#,
+///     QuotedArgs::new(&["cp", "a b", "c\td"]).to_string(),
+/// );
+/// ```
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+#[derive(Debug)]
+pub struct QuotedArgs<'a, T> {
+    args: &'a [T],
+    quoting_style: Option<QuotingStyle>,
+}
+
+impl<'a, T> QuotedArgs<'a, T>
+where
+    T: AsRef<OsStr>,
+{
+    /// Creates a wrapper for displaying the given arguments.
+    #[inline]
+    #[must_use]
+    pub const fn new(args: &'a [T]) -> Self {
+        Self {
+            args,
+            quoting_style: None,
+        }
+    }
+
+    /// Quotes each argument using a quoting style from another program.
+    ///
+    /// By default, the format of this crate is used.
+    #[inline]
+    #[must_use]
+    pub const fn quoting_style(mut self, style: QuotingStyle) -> Self {
+        self.quoting_style = Some(style);
+        self
+    }
+}
+
+// These implementations do not require the elements to be copyable.
+impl<T> Clone for QuotedArgs<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for QuotedArgs<'_, T> {}
+
+impl<T> Display for QuotedArgs<'_, T>
+where
+    T: AsRef<OsStr>,
+{
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, arg) in self.args.iter().enumerate() {
+            if i != 0 {
+                f.write_str(" ")?;
+            }
+            write_value(self.quoting_style, arg, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays environment variables, with one `NAME=VALUE` pair per line.
+///
+/// Names and values are quoted separately. Since both are enclosed in quotes
+/// by default, an `=` or a newline within a name or value cannot be confused
+/// with the structure of the output. No newline is written after the last
+/// variable.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+///
+/// use uniquote::QuotedEnv;
+///
+/// let vars: Vec<_> = env::vars_os().collect();
+/// println!("{}", QuotedEnv::new(&vars));
+///
+/// assert_eq!(
+///     "\"A\"=\"1=2\"\n\"B\"=\"x{~n}y\"",
+///     QuotedEnv::new(&[("A", "1=2"), ("B", "x\ny")]).to_string(),
+/// );
+/// ```
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+#[derive(Debug)]
+pub struct QuotedEnv<'a, K, V> {
+    vars: &'a [(K, V)],
+    quoting_style: Option<QuotingStyle>,
+}
+
+impl<'a, K, V> QuotedEnv<'a, K, V>
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    /// Creates a wrapper for displaying the given variables.
+    #[inline]
+    #[must_use]
+    pub const fn new(vars: &'a [(K, V)]) -> Self {
+        Self {
+            vars,
+            quoting_style: None,
+        }
+    }
+
+    /// Quotes each name and value using a quoting style from another
+    /// program.
+    ///
+    /// By default, the format of this crate is used.
+    #[inline]
+    #[must_use]
+    pub const fn quoting_style(mut self, style: QuotingStyle) -> Self {
+        self.quoting_style = Some(style);
+        self
+    }
+}
+
+impl<K, V> Clone for QuotedEnv<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for QuotedEnv<'_, K, V> {}
+
+impl<K, V> Display for QuotedEnv<'_, K, V>
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.vars.iter().enumerate() {
+            if i != 0 {
+                f.write_str("\n")?;
+            }
+            write_value(self.quoting_style, name, f)?;
+            f.write_str("=")?;
+            write_value(self.quoting_style, value, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays a [`Command`] as a command line.
+///
+/// The working directory, environment changes, program, and arguments are
+/// displayed in the following form, where each value is quoted separately:
+///
+/// ```text
+/// cd DIR && env -u REMOVED NAME=VALUE PROGRAM ARGS...
+/// ```
+///
+/// The `cd` and `env` parts are omitted when there is no working directory
+/// or no environment changes, respectively. Variables are listed in the
+/// order returned by [`Command::get_envs`]. Clearing the environment using
+/// [`Command::env_clear`] cannot be detected, so it is not displayed.
+///
+/// By default, values are quoted using [`Quote::quote`]. Setting a
+/// [`QuotingStyle`] such as [`QuotingStyle::ShellEscape`] produces a command
+/// line that can be pasted into a shell.
+///
+/// # Examples
+///
+/// ```
+/// use std::process::Command;
+///
+/// use uniquote::QuotedCommand;
+/// use uniquote::QuotingStyle;
+///
+/// let mut command = Command::new("grep");
+/// let _ = command
+///     .args(["-r", "new\nline"])
+///     .current_dir("/tmp")
+///     .env("LC_ALL", "C")
+///     .env_remove("GREP_COLORS");
+/// assert_eq!(
+///     r#"cd "/tmp" && env -u "GREP_COLORS" "LC_ALL"="C" "grep" "-r" "new{~n}line""#,
+///     QuotedCommand::new(&command).to_string(),
+/// );
+/// assert_eq!(
+///     r"cd /tmp && env -u GREP_COLORS LC_ALL=C grep -r 'new'$'\n''line'",
+///     QuotedCommand::new(&command)
+///         .quoting_style(QuotingStyle::ShellEscape)
+///         .to_string(),
+/// );
+/// ```
+///
+/// [`Quote::quote`]: super::Quote::quote
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+#[derive(Clone, Copy, Debug)]
+pub struct QuotedCommand<'a> {
+    command: &'a Command,
+    quoting_style: Option<QuotingStyle>,
+}
+
+impl<'a> QuotedCommand<'a> {
+    /// Creates a wrapper for displaying the given command.
+    #[inline]
+    #[must_use]
+    pub const fn new(command: &'a Command) -> Self {
+        Self {
+            command,
+            quoting_style: None,
+        }
+    }
+
+    /// Quotes each value using a quoting style from another program.
+    ///
+    /// By default, the format of this crate is used.
+    #[inline]
+    #[must_use]
+    pub const fn quoting_style(mut self, style: QuotingStyle) -> Self {
+        self.quoting_style = Some(style);
+        self
+    }
+
+    fn write_value<T>(&self, value: &T, f: &mut Formatter<'_>) -> fmt::Result
+    where
+        T: AsRef<OsStr> + ?Sized,
+    {
+        write_value(self.quoting_style, value, f)
+    }
+}
+
+impl Display for QuotedCommand<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(dir) = self.command.get_current_dir() {
+            f.write_str("cd ")?;
+            self.write_value(dir, f)?;
+            f.write_str(" && ")?;
+        }
+
+        let mut envs = self.command.get_envs().peekable();
+        if envs.peek().is_some() {
+            f.write_str("env ")?;
+            // Removals must precede assignments, since "env" processes
+            // options first.
+            let (removed, set): (Vec<_>, Vec<_>) =
+                envs.partition(|(_, value)| value.is_none());
+            for (name, _) in removed {
+                f.write_str("-u ")?;
+                self.write_value(name, f)?;
+                f.write_str(" ")?;
+            }
+            for (name, value) in set {
+                self.write_value(name, f)?;
+                f.write_str("=")?;
+                self.write_value(value.expect("missing value"), f)?;
+                f.write_str(" ")?;
+            }
+        }
+
+        self.write_value(self.command.get_program(), f)?;
+        for arg in self.command.get_args() {
+            f.write_str(" ")?;
+            self.write_value(arg, f)?;
+        }
+        Ok(())
+    }
+}
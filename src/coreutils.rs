@@ -0,0 +1,379 @@
+use core::fmt;
+use core::str;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::escape;
+use super::git;
+
+/// A quoting style used by another program.
+///
+/// Most of these styles correspond to the values accepted by the
+/// `--quoting-style` option of `ls` from GNU coreutils. When one is selected,
+/// the bytes represented by a quoted value are formatted the same way that
+/// `ls` would format a file name with those bytes, using a UTF-8 locale. The
+/// remaining styles are used by Git. The format described in [the
+/// module-level documentation][format] is not used.
+///
+/// Since the result must be valid UTF-8, invalid bytes and unprintable
+/// characters that a style would output literally are replaced with `?`. This
+/// matches the output of `ls` when its `--hide-control-chars` option is
+/// enabled, which is the default when writing to a terminal. Whether a
+/// non-ASCII character is printable is determined by this crate, so it may
+/// differ from the locale used by coreutils.
+///
+/// If the bytes cannot be determined, such as when a surrogate code point was
+/// escaped, the value is quoted as usual.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Quote;
+/// use uniquote::QuotingStyle;
+///
+/// let name = "new\nfile's";
+/// assert_eq!(
+///     r#"'new'$'\n''file'\''s'"#,
+///     name.quote().quoting_style(QuotingStyle::ShellEscape).to_string(),
+/// );
+/// assert_eq!(
+///     r#""new\nfile's""#,
+///     name.quote().quoting_style(QuotingStyle::C).to_string(),
+/// );
+/// ```
+///
+/// [format]: super#format
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum QuotingStyle {
+    /// The `literal` style, which outputs strings without quoting them.
+    Literal,
+
+    /// The `shell` style, which quotes strings using single quotes only when
+    /// necessary.
+    Shell,
+
+    /// The `shell-always` style, which always quotes strings using single
+    /// quotes.
+    ShellAlways,
+
+    /// The `shell-escape` style, which is similar to [`Shell`] but also
+    /// escapes unprintable characters using `$'...'` syntax.
+    ///
+    /// [`Shell`]: Self::Shell
+    ShellEscape,
+
+    /// The `shell-escape-always` style, which is similar to [`ShellAlways`]
+    /// but also escapes unprintable characters using `$'...'` syntax.
+    ///
+    /// [`ShellAlways`]: Self::ShellAlways
+    ShellEscapeAlways,
+
+    /// The `c` style, which quotes strings as C string literals.
+    C,
+
+    /// The `escape` style, which is similar to [`C`] but omits the
+    /// surrounding quotes.
+    ///
+    /// [`C`]: Self::C
+    Escape,
+
+    /// The style used by Git for paths, such as in the output of `git diff`
+    /// and `git ls-files`.
+    ///
+    /// Paths are quoted only when they contain bytes that are not printable
+    /// ASCII characters, or they contain `"` or `\`. Those bytes are escaped
+    /// using C-style escapes, matching the default value of the
+    /// `core.quotePath` configuration option. Paths quoted using this style
+    /// can be decoded by [`unquote_git`].
+    ///
+    /// [`unquote_git`]: super::unquote_git
+    Git,
+
+    /// The style used by Git for paths in the output of `git status
+    /// --porcelain`.
+    ///
+    /// This style is the same as [`Git`], except that paths containing
+    /// spaces are also quoted.
+    ///
+    /// [`Git`]: Self::Git
+    GitStatus,
+}
+
+impl QuotingStyle {
+    pub(super) fn write(
+        self,
+        bytes: &[u8],
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let mut quoted = Vec::with_capacity(bytes.len() + 2);
+        match self {
+            Self::Literal => quoted.extend_from_slice(bytes),
+            Self::Shell | Self::ShellEscape
+                if !needs_shell_quotes(bytes, self == Self::ShellEscape) =>
+            {
+                quoted.extend_from_slice(bytes);
+            }
+            Self::Shell | Self::ShellAlways => {
+                quote_shell(bytes, false, &mut quoted);
+            }
+            Self::ShellEscape | Self::ShellEscapeAlways => {
+                quote_shell(bytes, true, &mut quoted);
+            }
+            Self::C => quote_c(bytes, true, &mut quoted),
+            Self::Escape => quote_c(bytes, false, &mut quoted),
+            Self::Git => git::quote(bytes, false, &mut quoted),
+            Self::GitStatus => git::quote(bytes, true, &mut quoted),
+        }
+        f.write_str(&hide_unprintable(&quoted))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Char(char),
+    Invalid,
+}
+
+impl Unit {
+    fn is_printable(self) -> bool {
+        match self {
+            Self::Char(ch) => escape::is_printable(ch),
+            Self::Invalid => false,
+        }
+    }
+}
+
+// Invalid bytes are handled individually, similarly to "mbrtoc32".
+fn units(bytes: &[u8]) -> impl Iterator<Item = (usize, Unit, &[u8])> + '_ {
+    let mut index = 0;
+    bytes
+        .utf8_chunks()
+        .flat_map(|chunk| {
+            let valid = chunk.valid();
+            valid
+                .char_indices()
+                .map(move |(i, ch)| {
+                    let ch_len = ch.len_utf8();
+                    (Unit::Char(ch), &valid.as_bytes()[i..i + ch_len])
+                })
+                .chain(
+                    chunk
+                        .invalid()
+                        .chunks(1)
+                        .map(|byte| (Unit::Invalid, byte)),
+                )
+        })
+        .map(move |(unit, unit_bytes)| {
+            let i = index;
+            index += unit_bytes.len();
+            (i, unit, unit_bytes)
+        })
+}
+
+const fn sequence(ch: char) -> Option<u8> {
+    Some(match ch {
+        '\x07' => b'a',
+        '\x08' => b'b',
+        '\x0C' => b'f',
+        '\n' => b'n',
+        '\r' => b'r',
+        '\t' => b't',
+        '\x0B' => b'v',
+        _ => return None,
+    })
+}
+
+fn is_shell_special(ch: char) -> bool {
+    matches!(
+        ch,
+        ' ' | '!'
+            | '"'
+            | '$'
+            | '&'
+            | '\''
+            | '('
+            | ')'
+            | '*'
+            | ';'
+            | '<'
+            | '='
+            | '>'
+            | '?'
+            | '['
+            | '\\'
+            | '^'
+            | '`'
+            | '|'
+    )
+}
+
+fn needs_shell_quotes(bytes: &[u8], escape: bool) -> bool {
+    bytes.is_empty()
+        || units(bytes).any(|(i, unit, _)| match unit {
+            Unit::Char('\n' | '\r' | '\t') => true,
+            Unit::Char('#' | '~') => i == 0,
+            Unit::Char('{' | '}') => bytes.len() == 1,
+            Unit::Char(ch) if is_shell_special(ch) => true,
+            Unit::Char('\0') => escape,
+            unit => escape && !unit.is_printable(),
+        })
+}
+
+pub(super) fn push_octal(byte: u8, output: &mut Vec<u8>) {
+    output.extend([
+        b'0' + (byte >> 6),
+        b'0' + ((byte >> 3) & 7),
+        b'0' + (byte & 7),
+    ]);
+}
+
+// Escapes are written in "$'...'" strings, which are ended only once a
+// character is written literally.
+struct ShellWriter<'a> {
+    output: &'a mut Vec<u8>,
+    escaping: bool,
+}
+
+impl ShellWriter<'_> {
+    fn push_escape(&mut self, escape: &[u8]) {
+        if !self.escaping {
+            self.output.extend_from_slice(b"'$'");
+            self.escaping = true;
+        }
+        self.output.push(b'\\');
+        self.output.extend_from_slice(escape);
+    }
+
+    fn push_literal(&mut self, literal: &[u8]) {
+        if self.escaping {
+            self.output.extend_from_slice(b"''");
+            self.escaping = false;
+        }
+        self.output.extend_from_slice(literal);
+    }
+}
+
+fn quote_shell(bytes: &[u8], escape: bool, output: &mut Vec<u8>) {
+    let start = output.len();
+    output.push(b'\'');
+
+    let mut has_single_quote = false;
+    // Whether the string could be quoted using the C style instead.
+    let mut c_compatible = true;
+
+    let mut writer = ShellWriter {
+        output,
+        escaping: false,
+    };
+    for (i, unit, unit_bytes) in units(bytes) {
+        let compatible = match unit {
+            Unit::Char('\0') if escape => {
+                writer.push_escape(b"0");
+                false
+            }
+            Unit::Char(ch) if escape && sequence(ch).is_some() => {
+                writer.push_escape(&[sequence(ch).unwrap()]);
+                false
+            }
+            Unit::Char('\'') => {
+                has_single_quote = true;
+                writer.output.extend_from_slice(b"'\\'");
+                writer.escaping = false;
+                writer.push_literal(b"'");
+                true
+            }
+            unit if escape && !unit.is_printable() => {
+                for &byte in unit_bytes {
+                    let mut octal = Vec::with_capacity(3);
+                    push_octal(byte, &mut octal);
+                    writer.push_escape(&octal);
+                }
+                false
+            }
+            unit => {
+                writer.push_literal(unit_bytes);
+                match unit {
+                    Unit::Char('#' | '~') => i == 0,
+                    Unit::Char('{' | '}') => bytes.len() == 1,
+                    Unit::Char(ch) => {
+                        ch == ' '
+                            || (!is_shell_special(ch)
+                                && !ch.is_ascii_control()
+                                && escape::is_printable(ch))
+                    }
+                    Unit::Invalid => false,
+                }
+            }
+        };
+        c_compatible &= compatible;
+    }
+
+    // Single quotes are common in file names, so they are displayed without
+    // escaping when possible.
+    if has_single_quote && c_compatible {
+        output.truncate(start);
+        return quote_c(bytes, true, output);
+    }
+    output.push(b'\'');
+}
+
+fn quote_c(bytes: &[u8], quoted: bool, output: &mut Vec<u8>) {
+    if quoted {
+        output.push(b'"');
+    }
+    for (i, unit, unit_bytes) in units(bytes) {
+        match unit {
+            Unit::Char('\0') => {
+                output.extend_from_slice(b"\\0");
+                // Prevent the following digit from being parsed as part of
+                // an octal escape.
+                if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                    output.extend_from_slice(b"00");
+                }
+            }
+            Unit::Char('\\') => output.extend_from_slice(b"\\\\"),
+            Unit::Char('"') if quoted => output.extend_from_slice(b"\\\""),
+            Unit::Char(' ') if !quoted => output.extend_from_slice(b"\\ "),
+            Unit::Char(ch) if sequence(ch).is_some() => {
+                output.push(b'\\');
+                output.push(sequence(ch).unwrap());
+            }
+            unit if !unit.is_printable() => {
+                for &byte in unit_bytes {
+                    output.push(b'\\');
+                    push_octal(byte, output);
+                }
+            }
+            _ => output.extend_from_slice(unit_bytes),
+        }
+    }
+    if quoted {
+        output.push(b'"');
+    }
+}
+
+// This replacement is done after quoting, the same way as by "ls".
+fn hide_unprintable(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len());
+    let mut chunks = bytes.utf8_chunks().peekable();
+    while let Some(chunk) = chunks.next() {
+        for ch in chunk.valid().chars() {
+            output.push(if escape::is_printable(ch) { ch } else { '?' });
+        }
+
+        let invalid = chunk.invalid();
+        // A truncated character at the end of the string is replaced once.
+        let count = if chunks.peek().is_none()
+            && str::from_utf8(invalid).is_err_and(|x| x.error_len().is_none())
+        {
+            1
+        } else {
+            invalid.len()
+        };
+        output.extend((0..count).map(|_| '?'));
+    }
+    output
+}
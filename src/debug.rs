@@ -0,0 +1,301 @@
+use core::result;
+use core::str::Chars;
+use core::str::FromStr;
+
+use alloc::vec::Vec;
+
+use super::unquote::ErrorKind;
+use super::Formatter;
+use super::ParseError;
+use super::Quote;
+use super::Result;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Part {
+    Bytes(Vec<u8>),
+    Surrogate(u16),
+}
+
+/// A string parsed from the output of a [`Debug`] implementation.
+///
+/// The syntax of Rust string literals is accepted, with the escapes written
+/// by [`Debug`] for [`str`]. Escapes for invalid bytes (e.g., `\x80`) and
+/// unpaired surrogates (e.g., `\u{d800}`) are also accepted, since they are
+/// written by the implementations for [`OsStr`] and [`Path`] on different
+/// platforms.
+///
+/// This struct implements [`Quote`], so it can be used to convert the string
+/// to the format of this crate.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::DebugString;
+/// use uniquote::Quote;
+///
+/// let string: DebugString = r#""fo\x80o\u{feff}\n""#.parse()?;
+/// assert_eq!(r#""fo{~x80}o{~ufeff}{~n}""#, string.quote().to_string());
+/// #
+/// # Ok::<_, uniquote::ParseError>(())
+/// ```
+///
+/// [`Debug`]: ::core::fmt::Debug
+/// [`OsStr`]: ::std::ffi::OsStr
+/// [`Path`]: ::std::path::Path
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DebugString(Vec<Part>);
+
+impl DebugString {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        if let Some(Part::Bytes(last)) = self.0.last_mut() {
+            last.extend_from_slice(bytes);
+        } else {
+            self.0.push(Part::Bytes(bytes.to_vec()));
+        }
+    }
+
+    // Returns [None] if the string contains surrogates.
+    fn into_bytes(mut self) -> Option<Vec<u8>> {
+        match self.0.pop() {
+            Some(Part::Bytes(bytes)) if self.0.is_empty() => Some(bytes),
+            None => Some(Vec::new()),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DebugString {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(string: &str) -> result::Result<Self, Self::Err> {
+        parse(string, true)
+    }
+}
+
+impl Quote for DebugString {
+    #[inline]
+    fn escape(&self, f: &mut Formatter<'_>) -> Result {
+        for part in &self.0 {
+            match part {
+                Part::Bytes(bytes) => f.escape_bytes(bytes)?,
+                Part::Surrogate(surrogate) => {
+                    f.escape_code_point((*surrogate).into())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Chars<'a>,
+}
+
+impl Parser<'_> {
+    fn error(&self, kind: ErrorKind, offset: usize) -> ParseError {
+        ParseError::new(kind, self.input.as_bytes(), offset)
+    }
+
+    fn offset(&self) -> usize {
+        self.input.len() - self.chars.as_str().len()
+    }
+
+    fn next(&mut self) -> result::Result<char, ParseError> {
+        self.chars.next().ok_or_else(|| {
+            self.error(ErrorKind::UnterminatedEscape, self.offset())
+        })
+    }
+
+    /// Parses the next hex digit, failing if another digit is not allowed
+    /// at this position.
+    fn next_hex(&mut self, allowed: bool) -> result::Result<u32, ParseError> {
+        let offset = self.offset();
+        let digit = self.next()?;
+        digit
+            .to_digit(16)
+            .filter(|_| allowed)
+            .ok_or_else(|| self.error(ErrorKind::InvalidEscape, offset))
+    }
+}
+
+fn parse(
+    string: &str,
+    allow_surrogates: bool,
+) -> result::Result<DebugString, ParseError> {
+    let mut parser = Parser {
+        input: string,
+        chars: string.chars(),
+    };
+    if parser.chars.next() != Some('"') {
+        return Err(parser.error(ErrorKind::MissingQuote, 0));
+    }
+
+    let mut value = DebugString(Vec::new());
+    loop {
+        let start = parser.offset();
+        let ch = match parser.chars.next() {
+            Some('"') => {
+                let offset = parser.offset();
+                return if offset == string.len() {
+                    Ok(value)
+                } else {
+                    Err(parser.error(ErrorKind::TrailingCharacters, offset))
+                };
+            }
+            Some('\\') => parser.next()?,
+            Some(ch) => {
+                value.push_bytes(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                continue;
+            }
+            None => {
+                return Err(
+                    parser.error(ErrorKind::MissingQuote, string.len())
+                );
+            }
+        };
+
+        let ch = match ch {
+            '0' => '\0',
+            't' => '\t',
+            'r' => '\r',
+            'n' => '\n',
+            '\\' | '"' | '\'' => ch,
+            'x' => {
+                let byte =
+                    (parser.next_hex(true)? << 4) | parser.next_hex(true)?;
+                value.push_bytes(&[byte as u8]);
+                continue;
+            }
+            'u' => {
+                let offset = parser.offset();
+                if parser.next()? != '{' {
+                    return Err(parser.error(ErrorKind::InvalidEscape, offset));
+                }
+
+                let mut code_point = parser.next_hex(true)?;
+                for digits in 2..=7 {
+                    if parser.chars.as_str().starts_with('}') {
+                        break;
+                    }
+                    code_point =
+                        (code_point << 4) | parser.next_hex(digits <= 6)?;
+                }
+                let _ = parser.next()?;
+
+                if let Some(ch) = char::from_u32(code_point) {
+                    ch
+                } else if allow_surrogates
+                    && (0xD800..=0xDFFF).contains(&code_point)
+                {
+                    value.0.push(Part::Surrogate(code_point as u16));
+                    continue;
+                } else {
+                    return Err(
+                        parser.error(ErrorKind::InvalidCodePoint, start)
+                    );
+                }
+            }
+            _ => {
+                return Err(parser.error(ErrorKind::InvalidEscape, start + 1));
+            }
+        };
+        value.push_bytes(ch.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+/// Decodes a string formatted using [`Debug`].
+///
+/// The accepted syntax is described for [`DebugString`].
+///
+/// # Errors
+///
+/// Returns an error if the string is not quoted correctly, or if it contains
+/// an escaped surrogate.
+///
+/// # Examples
+///
+/// ```
+/// let string = b"fo\x80o\n";
+/// # #[cfg(unix)]
+/// # {
+/// # use std::ffi::OsStr;
+/// # use std::os::unix::ffi::OsStrExt;
+/// #
+/// # let quoted = format!("{:?}", OsStr::from_bytes(string));
+/// # assert_eq!(r#""fo\x80o\n""#, quoted);
+/// # }
+/// assert_eq!(
+///     Ok(&string[..]),
+///     uniquote::unquote_debug(r#""fo\x80o\n""#).as_deref(),
+/// );
+/// ```
+///
+/// [`Debug`]: ::core::fmt::Debug
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+pub fn unquote_debug(string: &str) -> result::Result<Vec<u8>, ParseError> {
+    parse(string, false)
+        .map(|x| x.into_bytes().expect("surrogates were parsed unexpectedly"))
+}
+
+#[cfg(feature = "os_str_bytes")]
+mod os_str {
+    use std::ffi::OsString;
+
+    use super::super::unquote::ErrorKind;
+    use super::super::ParseError;
+
+    /// Decodes a string formatted using [`Debug`] into an [`OsString`].
+    ///
+    /// This function is equivalent to [`unquote_debug`], but escaped
+    /// surrogates are accepted on platforms where they are valid in an
+    /// [`OsString`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not quoted correctly, or if the
+    /// decoded string cannot be represented on this platform.
+    ///
+    /// [`Debug`]: ::core::fmt::Debug
+    /// [`unquote_debug`]: super::unquote_debug
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+    pub fn unquote_debug_os_string(
+        string: &str,
+    ) -> Result<OsString, ParseError> {
+        let error = || {
+            ParseError::new(ErrorKind::InvalidEncoding, string.as_bytes(), 0)
+        };
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStringExt;
+            use std::str;
+
+            use super::Part;
+
+            let mut wide = Vec::new();
+            for part in super::parse(string, true)?.0 {
+                match part {
+                    Part::Bytes(bytes) => wide.extend(
+                        str::from_utf8(&bytes)
+                            .map_err(|_| error())?
+                            .encode_utf16(),
+                    ),
+                    Part::Surrogate(surrogate) => wide.push(surrogate),
+                }
+            }
+            Ok(OsString::from_wide(&wide))
+        }
+        #[cfg(not(windows))]
+        {
+            use os_str_bytes::OsStringBytes;
+
+            OsString::from_io_vec(super::unquote_debug(string)?)
+                .ok_or_else(error)
+        }
+    }
+}
+#[cfg(feature = "os_str_bytes")]
+pub use os_str::unquote_debug_os_string;
@@ -0,0 +1,54 @@
+/// Derives an implementation of [`Quote`] for a struct or enum.
+///
+/// The generated implementation escapes each field in declaration order,
+/// using only the public [`Formatter`] API. Therefore, the types of all
+/// fields must implement [`Quote`] or be references to such types. Unit
+/// structs and unit variants are displayed as empty strings.
+///
+/// A type with a single field, such as a newtype, is displayed exactly like
+/// that field. When a type has multiple fields, a separator must be given
+/// using `#[quote(separator = "...")]`, since the fields could not be
+/// distinguished otherwise. It is escaped like any other string. For enums,
+/// the separator can be given for the entire type or overridden for a single
+/// variant.
+///
+/// Bounds are added for the types of all fields that use a generic
+/// parameter. The expanded code refers to this crate as `uniquote`, so it
+/// must not be renamed in "Cargo.toml" when this macro is used.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Quote;
+///
+/// #[derive(Quote)]
+/// struct FileName<'a>(&'a [u8]);
+///
+/// #[derive(Quote)]
+/// #[quote(separator = ":")]
+/// enum Location<'a> {
+///     File(FileName<'a>),
+///     Line { file: FileName<'a>, line: &'a str },
+///     #[quote(separator = "-")]
+///     Range(&'a str, &'a str),
+/// }
+///
+/// let file = FileName(b"new\nfile\x80");
+/// assert_eq!(r#""new{~n}file{~x80}""#, file.quote().to_string());
+///
+/// let location = Location::Line { file, line: "5" };
+/// assert_eq!(r#""new{~n}file{~x80}:5""#, location.quote().to_string());
+/// assert_eq!(r#""1-2""#, Location::Range("1", "2").quote().to_string());
+/// ```
+///
+/// Types with multiple fields require a separator:
+///
+/// ```compile_fail
+/// #[derive(uniquote::Quote)]
+/// struct Pair(char, char);
+/// ```
+///
+/// [`Formatter`]: super::Formatter
+/// [`Quote`]: trait@super::Quote
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "derive")))]
+pub use uniquote_derive::Quote;
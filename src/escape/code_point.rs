@@ -10,6 +10,12 @@ impl From<char> for CodePoint {
     }
 }
 
+impl From<u32> for CodePoint {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
 impl From<DecodeUtf16Error> for CodePoint {
     fn from(value: DecodeUtf16Error) -> Self {
         Self(value.unpaired_surrogate().into())
@@ -1,11 +1,12 @@
 use core::char;
 use core::fmt;
-use core::fmt::Formatter;
-use core::fmt::Write as _;
+use core::fmt::Display;
 
 #[cfg(feature = "os_str_bytes")]
 use os_str_bytes::OsUnit;
 
+use super::Formatter;
+use super::Notation;
 use super::END_ESCAPE;
 use super::QUOTE;
 use super::START_ESCAPE;
@@ -13,9 +14,19 @@ use super::START_ESCAPE;
 mod code_point;
 use code_point::CodePoint;
 
+mod segments;
+pub use segments::Segment;
+pub use segments::Segments;
+
+mod stream;
+pub use stream::StreamEscaper;
+
 mod tables;
 use tables::UNPRINTABLE;
 
+mod utf8;
+use utf8::Decoder;
+
 fn table_contains(table: &[(u32, u32)], code_point: CodePoint) -> bool {
     let code_point = code_point.into();
     table
@@ -28,98 +39,153 @@ fn table_contains(table: &[(u32, u32)], code_point: CodePoint) -> bool {
         })
 }
 
-fn is_printable(ch: char) -> bool {
+pub(super) fn is_printable(ch: char) -> bool {
     // ASCII is very common, so it should be optimized.
     (' '..='~').contains(&ch)
         || (!ch.is_ascii() && !table_contains(UNPRINTABLE, ch.into()))
 }
 
-enum EscapedCodePoint {
-    Hex { value: u64, byte: bool },
-    Literal { ch: char, escape: bool },
-    Quote(),
-    Sequence(&'static str),
+/// A classification of an escape written by [`Quote::escape`].
+///
+/// Each variant describes the reason that a part of the string was escaped.
+/// The [`Display`] implementation writes the escape exactly as it would
+/// appear in quoted output.
+///
+/// [`Quote::escape`]: super::Quote::escape
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum EscapeKind {
+    /// A curly brace, which is escaped by doubling it (e.g., `{{`).
+    Brace(char),
+
+    /// A byte that is not part of valid UTF-8 (e.g., `{~x80}`).
+    Byte(u8),
+
+    /// An unprintable code point or a surrogate (e.g., `{~u200b}`).
+    CodePoint(u32),
+
+    /// A unit of an [`OsStr`] that is not part of valid Unicode (e.g.,
+    /// `{~ud800}`).
+    ///
+    /// The value is the one returned by [`os_str_bytes::OsUnit::to_u64`].
+    ///
+    /// [`OsStr`]: ::std::ffi::OsStr
+    OsUnit(u64),
+
+    /// A double quote (i.e., `{"}`).
+    Quote,
+
+    /// A control character with a short sequence (e.g., `{~n}`).
+    ///
+    /// The value is the control character.
+    Sequence(char),
 }
 
-impl EscapedCodePoint {
-    fn new(ch: char, byte: bool) -> Self {
-        match ch {
-            '\t' => Self::Sequence("t"),
-            '\n' => Self::Sequence("n"),
-            '\r' => Self::Sequence("r"),
-
-            QUOTE => Self::Quote(),
-            END_ESCAPE | START_ESCAPE => Self::Literal { ch, escape: true },
-
-            _ if is_printable(ch) => Self::Literal { ch, escape: false },
-            _ => Self::Hex {
-                value: ch.into(),
-                byte,
-            },
+impl EscapeKind {
+    // Returns the prefix and value of an escape that can be written using a
+    // hexadecimal number.
+    pub(super) fn unit(self) -> Option<(char, u64)> {
+        match self {
+            Self::Byte(value) => Some(('x', value.into())),
+            Self::CodePoint(value) => Some(('u', value.into())),
+            Self::OsUnit(value) => {
+                Some((if value <= 0xFF { 'x' } else { 'u' }, value))
+            }
+            Self::Sequence(ch) if self.sequence().is_none() => {
+                Some(('u', u32::from(ch).into()))
+            }
+            _ => None,
         }
     }
 
-    fn format(self, f: &mut Formatter<'_>) -> fmt::Result {
-        if let Self::Literal { ch, escape } = self {
-            for _ in 0..=(escape.into()) {
-                f.write_char(ch)?;
-            }
-            return Ok(());
+    pub(super) fn sequence(self) -> Option<char> {
+        match self {
+            Self::Sequence('\t') => Some('t'),
+            Self::Sequence('\n') => Some('n'),
+            Self::Sequence('\r') => Some('r'),
+            _ => None,
         }
+    }
+}
 
-        f.write_char(START_ESCAPE)?;
+impl Display for EscapeKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Notation::braces().write(*self, f)
+    }
+}
 
-        if matches!(self, Self::Quote()) {
-            f.write_char(QUOTE)?;
-        } else {
-            f.write_char('~')?;
+enum EscapedCodePoint {
+    Escape(EscapeKind),
+    Literal(char),
+}
 
-            match self {
-                Self::Hex { value, byte } => {
-                    write!(f, "{}{:x}", if byte { 'x' } else { 'u' }, value)?;
-                }
-                Self::Sequence(sequence) => f.write_str(sequence)?,
-                _ => unreachable!(),
+impl EscapedCodePoint {
+    fn format(self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Escape(EscapeKind::Sequence('\n'))
+                if f.options.literal_newlines =>
+            {
+                f.write_line_break()
+            }
+            Self::Escape(EscapeKind::Sequence('\t'))
+                if f.options.literal_tabs =>
+            {
+                f.write_char('\t')
             }
+            Self::Escape(kind) => f.write_escape(kind),
+            Self::Literal(ch) => f.write_char(ch),
         }
-
-        f.write_char(END_ESCAPE)
     }
 }
 
 impl From<u8> for EscapedCodePoint {
     fn from(value: u8) -> Self {
-        Self::new(value.into(), true)
+        match char::from(value).into() {
+            Self::Escape(EscapeKind::CodePoint(_)) => {
+                Self::Escape(EscapeKind::Byte(value))
+            }
+            code_point => code_point,
+        }
     }
 }
 
 impl From<char> for EscapedCodePoint {
     fn from(value: char) -> Self {
-        Self::new(value, false)
+        Self::Escape(match value {
+            '\t' | '\n' | '\r' => EscapeKind::Sequence(value),
+
+            QUOTE => EscapeKind::Quote,
+            END_ESCAPE | START_ESCAPE => EscapeKind::Brace(value),
+
+            _ if is_printable(value) => return Self::Literal(value),
+            _ => EscapeKind::CodePoint(value.into()),
+        })
     }
 }
 
 impl From<CodePoint> for EscapedCodePoint {
     fn from(value: CodePoint) -> Self {
-        // Upon error, [value] is known to be a surrogate, so it is
-        // unprintable.
-        char::try_from(value)
-            .map(Into::into)
-            .unwrap_or_else(|_| Self::Hex {
-                value: value.into(),
-                byte: false,
-            })
+        // Upon error, [value] is known to be a surrogate or out of range, so
+        // it is unprintable.
+        char::try_from(value).map(Into::into).unwrap_or_else(|_| {
+            Self::Escape(EscapeKind::CodePoint(value.into()))
+        })
     }
 }
 
 #[cfg(feature = "os_str_bytes")]
 impl From<OsUnit> for EscapedCodePoint {
     fn from(value: OsUnit) -> Self {
-        let value = value.to_u64();
-        Self::Hex {
-            value,
-            byte: value <= 0xFF,
-        }
+        Self::Escape(EscapeKind::OsUnit(value.to_u64()))
+    }
+}
+
+fn format_invalid(byte: u8, f: &mut Formatter<'_>) -> fmt::Result {
+    if f.options.exact {
+        f.write_escape(EscapeKind::Byte(byte))
+    } else {
+        EscapedCodePoint::from(byte).format(f)
     }
 }
 
@@ -162,10 +228,7 @@ impl Escape for str {
             }
 
             let code_point = ch.into();
-            escaped = !matches!(
-                code_point,
-                EscapedCodePoint::Literal { escape: false, .. },
-            );
+            escaped = !matches!(code_point, EscapedCodePoint::Literal(_));
             if escaped {
                 push_literal!(i);
                 code_point.format(f)?;
@@ -185,7 +248,7 @@ impl Escape for [u8] {
             chunk.valid().escape(f)?;
 
             for &byte in chunk.invalid() {
-                EscapedCodePoint::from(byte).format(f)?;
+                format_invalid(byte, f)?;
             }
         }
         Ok(())
@@ -203,3 +266,28 @@ where
     }
     Ok(())
 }
+
+pub(super) fn byte_iter<I>(iter: I, f: &mut Formatter<'_>) -> fmt::Result
+where
+    I: IntoIterator<Item = u8>,
+{
+    let mut decoder = Decoder::default();
+    for byte in iter {
+        decoder.push(byte, f)?;
+    }
+    decoder.finish(f)
+}
+
+pub(super) fn chars<I>(iter: I, f: &mut Formatter<'_>) -> fmt::Result
+where
+    I: IntoIterator<Item = char>,
+{
+    for ch in iter {
+        EscapedCodePoint::from(ch).format(f)?;
+    }
+    Ok(())
+}
+
+pub(super) fn code_point(value: u32, f: &mut Formatter<'_>) -> fmt::Result {
+    EscapedCodePoint::from(CodePoint::from(value)).format(f)
+}
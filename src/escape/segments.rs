@@ -0,0 +1,240 @@
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::iter::FusedIterator;
+use core::slice;
+use core::str;
+use core::str::Utf8Chunks;
+
+#[cfg(feature = "os_str_bytes")]
+use std::ffi::OsStr;
+
+#[cfg(feature = "os_str_bytes")]
+use os_str_bytes::iter::OsUnits;
+#[cfg(feature = "os_str_bytes")]
+use os_str_bytes::OsStrBytesExt;
+
+use super::EscapeKind;
+use super::EscapedCodePoint;
+
+// Invalid bytes are displayed as Latin-1 characters when printable, but those
+// characters do not exist in the original string. This table allows them to
+// be borrowed anyway.
+const LATIN_1: &str = {
+    const BYTES: [u8; 0x100] = {
+        let mut bytes = [0; 0x100];
+        let mut i = 0;
+        while i < 0x80 {
+            let byte = 0x80 | i as u8;
+            bytes[2 * i] = 0xC0 | (byte >> 6);
+            bytes[2 * i + 1] = 0x80 | (byte & 0x3F);
+            i += 1;
+        }
+        bytes
+    };
+    match str::from_utf8(&BYTES) {
+        Ok(string) => string,
+        Err(_) => panic!("invalid Latin-1 table"),
+    }
+};
+
+/// A part of a string, as it would be written by [`Quote::escape`].
+///
+/// Instances are returned by [`Segments`].
+///
+/// [`Quote::escape`]: super::super::Quote::escape
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Segment<'a> {
+    /// Text that is output without modification.
+    Literal(&'a str),
+
+    /// A single escape.
+    Escape(EscapeKind),
+}
+
+impl Display for Segment<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(string) => f.write_str(string),
+            Self::Escape(kind) => kind.fmt(f),
+        }
+    }
+}
+
+impl From<EscapedCodePoint> for Segment<'_> {
+    fn from(value: EscapedCodePoint) -> Self {
+        match value {
+            EscapedCodePoint::Escape(kind) => Self::Escape(kind),
+            EscapedCodePoint::Literal(ch) => {
+                let index = 2 * (u32::from(ch) - 0x80) as usize;
+                Self::Literal(&LATIN_1[index..index + 2])
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Invalid<'a> {
+    Bytes(slice::Iter<'a, u8>),
+    #[cfg(feature = "os_str_bytes")]
+    OsUnits(OsUnits<'a>),
+}
+
+impl Iterator for Invalid<'_> {
+    type Item = EscapedCodePoint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Bytes(bytes) => bytes.next().map(|&x| x.into()),
+            #[cfg(feature = "os_str_bytes")]
+            Self::OsUnits(units) => units.next().map(Into::into),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Source<'a> {
+    Bytes(Utf8Chunks<'a>),
+    #[cfg(feature = "os_str_bytes")]
+    OsStr(os_str_bytes::iter::Utf8Chunks<'a>),
+}
+
+/// An iterator over the [`Segment`]s of an escaped string.
+///
+/// This struct allows inspecting the output of [`Quote::escape`] without
+/// parsing it. For example, escapes can be displayed in a different color
+/// than the rest of the string. Writing all segments in order is equivalent
+/// to calling [`Quote::escape`].
+///
+/// Instances can be created from the types that store strings directly, using
+/// the [`From`] implementations of this struct.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::EscapeKind;
+/// use uniquote::Segment;
+/// use uniquote::Segments;
+///
+/// let mut segments = Segments::from(&b"fo\x80o\n"[..]);
+/// assert_eq!(Some(Segment::Literal("fo")), segments.next());
+/// assert_eq!(
+///     Some(Segment::Escape(EscapeKind::Byte(0x80))),
+///     segments.next(),
+/// );
+/// assert_eq!(Some(Segment::Literal("o")), segments.next());
+/// assert_eq!(
+///     Some(Segment::Escape(EscapeKind::Sequence('\n'))),
+///     segments.next(),
+/// );
+/// assert_eq!(None, segments.next());
+/// ```
+///
+/// [`Quote::escape`]: super::super::Quote::escape
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct Segments<'a> {
+    front: Invalid<'a>,
+    valid: &'a str,
+    back: Invalid<'a>,
+    source: Source<'a>,
+}
+
+impl<'a> Segments<'a> {
+    fn new(source: Source<'a>) -> Self {
+        Self {
+            front: Invalid::Bytes([].iter()),
+            valid: "",
+            back: Invalid::Bytes([].iter()),
+            source,
+        }
+    }
+
+    fn next_valid(&mut self) -> Option<Segment<'a>> {
+        let mut chars = self.valid.char_indices();
+        let (_, ch) = chars.next()?;
+        let index = match ch.into() {
+            EscapedCodePoint::Escape(kind) => {
+                self.valid = chars.as_str();
+                return Some(Segment::Escape(kind));
+            }
+            EscapedCodePoint::Literal(_) => chars
+                .find(|&(_, x)| {
+                    !matches!(x.into(), EscapedCodePoint::Literal(_))
+                })
+                .map(|(i, _)| i)
+                .unwrap_or(self.valid.len()),
+        };
+        let (literal, valid) = self.valid.split_at(index);
+        self.valid = valid;
+        Some(Segment::Literal(literal))
+    }
+
+    fn next_chunk(&mut self) -> bool {
+        match &mut self.source {
+            Source::Bytes(chunks) => {
+                let Some(chunk) = chunks.next() else {
+                    return false;
+                };
+                self.valid = chunk.valid();
+                self.back = Invalid::Bytes(chunk.invalid().iter());
+            }
+            #[cfg(feature = "os_str_bytes")]
+            Source::OsStr(chunks) => {
+                let Some((invalid, valid)) = chunks.next() else {
+                    return false;
+                };
+                self.front = Invalid::OsUnits(invalid.os_units());
+                self.valid = valid;
+            }
+        }
+        true
+    }
+}
+
+impl<'a> From<&'a [u8]> for Segments<'a> {
+    #[inline]
+    fn from(value: &'a [u8]) -> Self {
+        Self::new(Source::Bytes(value.utf8_chunks()))
+    }
+}
+
+#[cfg(feature = "os_str_bytes")]
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+impl<'a> From<&'a OsStr> for Segments<'a> {
+    #[inline]
+    fn from(value: &'a OsStr) -> Self {
+        Self::new(Source::OsStr(value.utf8_chunks()))
+    }
+}
+
+impl<'a> From<&'a str> for Segments<'a> {
+    #[inline]
+    fn from(value: &'a str) -> Self {
+        value.as_bytes().into()
+    }
+}
+
+impl FusedIterator for Segments<'_> {}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(code_point) = self.front.next() {
+                return Some(code_point.into());
+            }
+            if let Some(segment) = self.next_valid() {
+                return Some(segment);
+            }
+            if let Some(code_point) = self.back.next() {
+                return Some(code_point.into());
+            }
+            if !self.next_chunk() {
+                return None;
+            }
+        }
+    }
+}
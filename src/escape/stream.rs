@@ -0,0 +1,129 @@
+use core::fmt;
+
+use super::super::formatter::Options;
+use super::utf8;
+use super::utf8::Decoder;
+use super::Escape;
+use super::Formatter;
+
+/// Escapes a byte string that is received in chunks.
+///
+/// Calling [`Quote::escape`] separately for each chunk would escape every
+/// character split between chunks. This struct stores incomplete UTF-8
+/// sequences until they are completed by the next chunk. The result of
+/// feeding all chunks is the same as escaping their concatenation.
+///
+/// Surrounding quotes are not written.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::StreamEscaper;
+///
+/// let mut escaper = StreamEscaper::new(String::new());
+/// escaper.feed(b"caf\xC3")?;
+/// escaper.feed(b"\xA9\n\xE2\x82")?;
+/// assert_eq!("café{~n}â{~x82}", escaper.finish()?);
+/// #
+/// # Ok::<_, std::fmt::Error>(())
+/// ```
+///
+/// [`Quote::escape`]: super::super::Quote::escape
+#[derive(Debug)]
+pub struct StreamEscaper<W> {
+    writer: W,
+    decoder: Decoder,
+    pub(crate) options: Options<'static>,
+}
+
+impl<W> StreamEscaper<W>
+where
+    W: fmt::Write,
+{
+    /// Creates a new escaper that writes to the given sink.
+    #[inline]
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            decoder: Decoder::default(),
+            options: Options::new(),
+        }
+    }
+
+    /// Escapes the next chunk of the string.
+    ///
+    /// Bytes at the end of the chunk will not be written if they could be
+    /// the start of a multibyte character.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if and only if writing to the sink fails.
+    pub fn feed(&mut self, mut bytes: &[u8]) -> fmt::Result {
+        let mut f = Formatter::with_options(&mut self.writer, self.options);
+
+        while !self.decoder.is_empty() {
+            let Some((&byte, rest)) = bytes.split_first() else {
+                return Ok(());
+            };
+            self.decoder.push(byte, &mut f)?;
+            bytes = rest;
+        }
+
+        let (complete, incomplete) =
+            bytes.split_at(utf8::incomplete_index(bytes));
+        complete.escape(&mut f)?;
+        for &byte in incomplete {
+            self.decoder.push(byte, &mut f)?;
+        }
+        Ok(())
+    }
+
+    /// Escapes any bytes stored from the last chunk and returns the sink.
+    ///
+    /// The stored bytes are always escaped, since the string has ended
+    /// before they could be completed.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if and only if writing to the sink fails.
+    #[inline]
+    pub fn finish(mut self) -> Result<W, fmt::Error> {
+        self.escape_incomplete()?;
+        Ok(self.writer)
+    }
+
+    pub(crate) fn escape_incomplete(&mut self) -> fmt::Result {
+        self.decoder.finish(&mut Formatter::with_options(
+            &mut self.writer,
+            self.options,
+        ))
+    }
+
+    /// Returns the sink without escaping bytes stored from the last chunk.
+    ///
+    /// Those bytes will be discarded. Use [`finish`] to escape them.
+    ///
+    /// [`finish`]: Self::finish
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Returns a reference to the sink.
+    #[inline]
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the sink.
+    ///
+    /// Text written directly to the sink will not be escaped.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
@@ -0,0 +1,95 @@
+use core::fmt;
+use core::str;
+
+use super::EscapedCodePoint;
+use super::Formatter;
+
+fn width(byte: u8) -> usize {
+    match byte {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => 0,
+    }
+}
+
+// These ranges are specified in Table 3-7 of the Unicode Standard.
+fn is_continuation(first: u8, index: usize, byte: u8) -> bool {
+    let range = match (index, first) {
+        (1, 0xE0) => 0xA0..=0xBF,
+        (1, 0xED) => 0x80..=0x9F,
+        (1, 0xF0) => 0x90..=0xBF,
+        (1, 0xF4) => 0x80..=0x8F,
+        _ => 0x80..=0xBF,
+    };
+    range.contains(&byte)
+}
+
+// Returns the index of a multibyte character truncated at the end of the
+// slice, or the slice length if there is none.
+pub(super) fn incomplete_index(bytes: &[u8]) -> usize {
+    for (i, &byte) in bytes.iter().enumerate().rev().take(3) {
+        let width = width(byte);
+        if width != 0 {
+            return if bytes.len() - i < width {
+                i
+            } else {
+                bytes.len()
+            };
+        } else if !(0x80..=0xBF).contains(&byte) {
+            break;
+        }
+    }
+    bytes.len()
+}
+
+// Decodes UTF-8 one byte at a time. Invalid bytes are escaped the same way as
+// by the implementation for [[u8]], since sequences are only split after
+// their longest valid prefix.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Decoder {
+    buffer: [u8; 4],
+    len: usize,
+}
+
+impl Decoder {
+    pub(super) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(super) fn push(
+        &mut self,
+        byte: u8,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        if self.len != 0 && !is_continuation(self.buffer[0], self.len, byte) {
+            self.finish(f)?;
+        }
+
+        self.buffer[self.len] = byte;
+        self.len += 1;
+
+        let width = width(self.buffer[0]);
+        if width == 0 {
+            return self.finish(f);
+        } else if self.len < width {
+            return Ok(());
+        }
+
+        let ch = str::from_utf8(&self.buffer[..self.len])
+            .ok()
+            .and_then(|x| x.chars().next())
+            .expect("invalid UTF-8 sequence");
+        self.len = 0;
+        EscapedCodePoint::from(ch).format(f)
+    }
+
+    pub(super) fn finish(&mut self, f: &mut Formatter<'_>) -> fmt::Result {
+        for &byte in &self.buffer[..self.len] {
+            super::format_invalid(byte, f)?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+}
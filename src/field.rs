@@ -0,0 +1,39 @@
+//! Helpers for recording quoted values in [`tracing`] events and spans.
+//!
+//! Values recorded using these helpers are formatted the same way as by
+//! [`Quote::quote`]. Subscribers receive the result as a formatted value, so
+//! they will usually store it as a string, leaving escapes intact.
+//!
+//! [`Quote::quote`]: super::Quote::quote
+
+use core::fmt::Display;
+
+use tracing::field::DisplayValue;
+
+use super::Quote;
+
+/// Quotes a value for use as a field.
+///
+/// This function is equivalent to wrapping the result of [`Quote::quote`]
+/// using [`tracing::field::display`], which is done by the `%` sigil in
+/// macros.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::field;
+///
+/// let path = "new\nfile";
+/// tracing::info!(path = field::quoted(path), "opened file");
+/// assert_eq!(r#""new{~n}file""#, format!("{:?}", field::quoted(path)));
+/// ```
+///
+/// [`Quote::quote`]: super::Quote::quote
+#[inline]
+#[must_use]
+pub fn quoted<T>(value: &T) -> DisplayValue<impl Display + '_>
+where
+    T: Quote + ?Sized,
+{
+    tracing::field::display(value.quote())
+}
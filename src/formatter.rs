@@ -1,9 +1,25 @@
 use core::fmt;
 use core::fmt::Display;
-use core::mem;
+use core::fmt::Write;
 use core::result;
 
 use super::escape;
+use super::escape::EscapeKind;
+use super::escape::Segment;
+#[cfg(feature = "alloc")]
+use super::unquote;
+#[cfg(feature = "alloc")]
+use super::Binary;
+#[cfg(feature = "color")]
+use super::Colors;
+use super::Notation;
+use super::Quote;
+#[cfg(feature = "alloc")]
+use super::QuotingStyle;
+use super::END_ESCAPE;
+use super::MAX_REPEAT;
+use super::QUOTE;
+use super::START_ESCAPE;
 
 /// The error type returned by [`Quote::escape`].
 ///
@@ -27,6 +43,67 @@ impl Display for Error {
 /// [`Quote::escape`]: super::Quote::escape
 pub type Result = result::Result<(), Error>;
 
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Options<'a> {
+    #[cfg(feature = "alloc")]
+    pub(super) binary: Option<Binary>,
+    #[cfg(feature = "color")]
+    pub(super) colors: Colors,
+    pub(super) compact: bool,
+    // Invalid bytes will always be escaped, so that the output can be decoded
+    // exactly.
+    pub(super) exact: bool,
+    pub(super) indent: usize,
+    pub(super) line_prefix: &'a str,
+    pub(super) literal_newlines: bool,
+    pub(super) literal_tabs: bool,
+    pub(super) notation: Notation,
+    #[cfg(feature = "alloc")]
+    pub(super) quoting_style: Option<QuotingStyle>,
+}
+
+impl Options<'_> {
+    pub(super) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "alloc")]
+            binary: None,
+            #[cfg(feature = "color")]
+            colors: Colors::NONE,
+            compact: false,
+            exact: false,
+            indent: 0,
+            line_prefix: "",
+            literal_newlines: false,
+            literal_tabs: false,
+            notation: Notation::braces(),
+            #[cfg(feature = "alloc")]
+            quoting_style: None,
+        }
+    }
+}
+
+// Receives the classified output of [Quote::escape] instead of text.
+pub(super) trait SegmentWriter {
+    fn write_segment(&mut self, segment: Segment<'_>) -> fmt::Result;
+}
+
+pub(super) enum Writer<'a> {
+    Text(&'a mut dyn fmt::Write),
+    Segments(&'a mut dyn SegmentWriter),
+}
+
+impl Write for Writer<'_> {
+    #[inline]
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        match self {
+            Self::Text(writer) => writer.write_str(string),
+            Self::Segments(writer) => {
+                writer.write_segment(Segment::Literal(string))
+            }
+        }
+    }
+}
+
 /// The type passed between calls to [`Quote::escape`].
 ///
 /// All methods of this struct are defined to ensure that strings are quoted
@@ -34,13 +111,308 @@ pub type Result = result::Result<(), Error>;
 /// [`Quote::escape`] implementation of another type.
 ///
 /// [`Quote::escape`]: super::Quote::escape
-#[repr(transparent)]
-pub struct Formatter<'a>(pub(super) fmt::Formatter<'a>);
+pub struct Formatter<'a> {
+    pub(super) writer: Writer<'a>,
+    pub(super) options: Options<'a>,
+    group: Option<Group>,
+}
+
+// A compact escape that has been started but not yet ended. The last unit is
+// only written once it is known how many times it is repeated.
+#[derive(Clone, Copy)]
+struct Group {
+    prefix: char,
+    value: u64,
+    count: usize,
+    first: bool,
+}
 
 impl<'a> Formatter<'a> {
-    pub(super) fn new<'b>(f: &'b mut fmt::Formatter<'a>) -> &'b mut Self {
-        // SAFETY: This struct has a layout that makes this operation safe.
-        unsafe { mem::transmute(f) }
+    pub(super) fn new(writer: &'a mut dyn fmt::Write) -> Self {
+        Self::with_options(writer, Options::new())
+    }
+
+    pub(super) fn with_options(
+        writer: &'a mut dyn fmt::Write,
+        options: Options<'a>,
+    ) -> Self {
+        Self::with_writer(Writer::Text(writer), options)
+    }
+
+    // Invalid bytes are always classified as escapes, regardless of the
+    // options, since printing them as characters would change the string.
+    pub(super) fn with_segments(
+        writer: &'a mut dyn SegmentWriter,
+        mut options: Options<'a>,
+    ) -> Self {
+        options.exact = true;
+        Self::with_writer(Writer::Segments(writer), options)
+    }
+
+    fn with_writer(writer: Writer<'a>, options: Options<'a>) -> Self {
+        Self {
+            writer,
+            options,
+            group: None,
+        }
+    }
+
+    fn write_unit(&mut self, group: Group) -> fmt::Result {
+        if !group.first {
+            self.writer.write_char(' ')?;
+        }
+        self.options
+            .notation
+            .write_hex(group.value, 1, &mut self.writer)?;
+        if group.count > 1 {
+            write!(self.writer, "*{}", group.count)?;
+        }
+        Ok(())
+    }
+
+    fn write_grouped(&mut self, prefix: char, value: u64) -> fmt::Result {
+        match self.group {
+            Some(mut group) if group.prefix == prefix => {
+                if group.value == value && group.count < MAX_REPEAT {
+                    group.count += 1;
+                } else {
+                    self.write_unit(group)?;
+                    group.value = value;
+                    group.count = 1;
+                    group.first = false;
+                }
+                self.group = Some(group);
+                Ok(())
+            }
+            _ => {
+                self.end_group()?;
+                #[cfg(feature = "color")]
+                self.options.colors.start_escape(&mut self.writer)?;
+                write!(self.writer, "{}~{}", START_ESCAPE, prefix)?;
+                self.group = Some(Group {
+                    prefix,
+                    value,
+                    count: 1,
+                    first: true,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn end_group(&mut self) -> fmt::Result {
+        let Some(group) = self.group.take() else {
+            return Ok(());
+        };
+        self.write_unit(group)?;
+        self.writer.write_char(END_ESCAPE)?;
+        #[cfg(feature = "color")]
+        self.options.colors.end_escape(&mut self.writer)?;
+        Ok(())
+    }
+
+    pub(super) fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.end_group()?;
+        self.writer.write_str(string)
+    }
+
+    pub(super) fn write_char(&mut self, ch: char) -> fmt::Result {
+        self.end_group()?;
+        self.writer.write_char(ch)
+    }
+
+    fn write_quote(&mut self) -> fmt::Result {
+        self.end_group()?;
+        #[cfg(feature = "color")]
+        {
+            self.options
+                .colors
+                .write_quote(&mut self.writer, |f| f.write_char(QUOTE))
+        }
+        #[cfg(not(feature = "color"))]
+        self.writer.write_char(QUOTE)
+    }
+
+    fn write_line_start(&mut self) -> fmt::Result {
+        // This method is called for every quoted string.
+        if self.options.indent == 0 && self.options.line_prefix.is_empty() {
+            return Ok(());
+        }
+        write!(self.writer, "{:1$}", "", self.options.indent)?;
+        self.writer.write_str(self.options.line_prefix)
+    }
+
+    pub(super) fn write_line_break(&mut self) -> fmt::Result {
+        self.write_char('\n')?;
+        self.write_line_start()
+    }
+
+    pub(super) fn write_escape(&mut self, kind: EscapeKind) -> fmt::Result {
+        if let Writer::Segments(writer) = &mut self.writer {
+            return writer.write_segment(Segment::Escape(kind));
+        }
+        if self.options.compact {
+            if let Some((prefix, value)) = self.options.notation.unit(kind) {
+                return self.write_grouped(prefix, value);
+            }
+        }
+        self.end_group()?;
+        let notation = self.options.notation;
+        #[cfg(feature = "color")]
+        {
+            self.options
+                .colors
+                .write_escape(&mut self.writer, |f| notation.write(kind, f))
+        }
+        #[cfg(not(feature = "color"))]
+        notation.write(kind, &mut self.writer)
+    }
+
+    pub(super) fn write_quoted<T>(&mut self, value: &T) -> fmt::Result
+    where
+        T: Quote + ?Sized,
+    {
+        self.write_line_start()?;
+        #[cfg(feature = "alloc")]
+        if let Some(style) = self.options.quoting_style {
+            // Values that cannot be represented as bytes, such as those
+            // containing surrogates, are quoted using the default format.
+            if let Some(bytes) = unquote::decode(value) {
+                return style.write(&bytes, &mut self.writer);
+            }
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(binary) = self.options.binary {
+            if binary.write(value, self)? {
+                return Ok(());
+            }
+        }
+        self.write_quote()?;
+        value.escape(self).map_err(|x| x.0)?;
+        self.write_quote()
+    }
+
+    /// Provides an implementation of [`Quote::escape`] for a byte string.
+    ///
+    /// This method is equivalent to calling [`Quote::escape`] for `[u8]`.
+    ///
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn escape_bytes(&mut self, bytes: &[u8]) -> Result {
+        bytes.escape(self)
+    }
+
+    /// Provides an implementation of [`Quote::escape`] for a byte string
+    /// iterator.
+    ///
+    /// The bytes are decoded incrementally, so the iterator does not need to
+    /// be collected beforehand. The result is the same as calling
+    /// [`escape_bytes`] with all bytes of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// struct Frames<'a>(&'a [&'a [u8]]);
+    ///
+    /// impl Quote for Frames<'_> {
+    ///     fn escape(&self, f: &mut uniquote::Formatter<'_>) -> uniquote::Result {
+    ///         f.escape_byte_iter(self.0.iter().flat_map(|x| x.iter().copied()))
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#""é{~x80}""#,
+    ///     Frames(&[b"\xC3", b"\xA9\x80"]).quote().to_string(),
+    /// );
+    /// ```
+    ///
+    /// [`escape_bytes`]: Self::escape_bytes
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn escape_byte_iter<I>(&mut self, iter: I) -> Result
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        escape::byte_iter(iter, self).map_err(Error)
+    }
+
+    /// Provides an implementation of [`Quote::escape`] for a single
+    /// character.
+    ///
+    /// This method is equivalent to calling [`Quote::escape`] for [`char`].
+    ///
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn escape_char(&mut self, ch: char) -> Result {
+        ch.escape(self)
+    }
+
+    /// Provides an implementation of [`Quote::escape`] for a character
+    /// iterator.
+    ///
+    /// The result is the same as calling [`escape_char`] for each character.
+    ///
+    /// [`escape_char`]: Self::escape_char
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn escape_chars<I>(&mut self, iter: I) -> Result
+    where
+        I: IntoIterator<Item = char>,
+    {
+        escape::chars(iter, self).map_err(Error)
+    }
+
+    /// Provides an implementation of [`Quote::escape`] for a single code
+    /// point.
+    ///
+    /// Unlike [`escape_char`], this method accepts surrogates and values
+    /// greater than [`char::MAX`]. They are always escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// struct CodePoints<'a>(&'a [u32]);
+    ///
+    /// impl Quote for CodePoints<'_> {
+    ///     fn escape(&self, f: &mut uniquote::Formatter<'_>) -> uniquote::Result {
+    ///         for &code_point in self.0 {
+    ///             f.escape_code_point(code_point)?;
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#""a{~ud800}{~u110000}""#,
+    ///     CodePoints(&[0x61, 0xD800, 0x110000]).quote().to_string(),
+    /// );
+    /// ```
+    ///
+    /// [`escape_char`]: Self::escape_char
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn escape_code_point(&mut self, code_point: u32) -> Result {
+        escape::code_point(code_point, self).map_err(Error)
+    }
+
+    /// Provides an implementation of [`Quote::escape`] for a UTF-32 string
+    /// iterator.
+    ///
+    /// The iterator does not need to contain valid UTF-32, since invalid
+    /// code points will be escaped as described for [`escape_code_point`].
+    ///
+    /// [`escape_code_point`]: Self::escape_code_point
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn escape_utf32<I>(&mut self, iter: I) -> Result
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        iter.into_iter().try_for_each(|x| self.escape_code_point(x))
     }
 
     /// Provides an implementation of [`Quote::escape`] for a UTF-16 string
@@ -55,7 +427,42 @@ impl<'a> Formatter<'a> {
     where
         I: IntoIterator<Item = u16>,
     {
-        escape::utf16(iter, &mut self.0).map_err(Error)
+        escape::utf16(iter, self).map_err(Error)
+    }
+
+    /// Writes a string to the output without escaping it.
+    ///
+    /// This method is only useful when the string is known to require no
+    /// escaping, such as when it was previously escaped. Otherwise, the output
+    /// may be ambiguous or may contain unprintable characters. Callers are
+    /// responsible for ensuring that the string does not contain any
+    /// characters that [`Quote::escape`] would escape.
+    ///
+    /// Although this method is not unsafe, it can be used to circumvent the
+    /// guarantees described in [the module-level documentation][format].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// struct Number(u32);
+    ///
+    /// impl Quote for Number {
+    ///     fn escape(&self, f: &mut uniquote::Formatter<'_>) -> uniquote::Result {
+    ///         // Digits are never escaped.
+    ///         f.write_literal_unchecked(&self.0.to_string())
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(r#""42""#, Number(42).quote().to_string());
+    /// ```
+    ///
+    /// [format]: super#format
+    /// [`Quote::escape`]: super::Quote::escape
+    #[inline]
+    pub fn write_literal_unchecked(&mut self, string: &str) -> Result {
+        self.write_str(string).map_err(Error)
     }
 }
 
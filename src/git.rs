@@ -0,0 +1,194 @@
+use alloc::vec::Vec;
+
+use super::coreutils;
+use super::unquote::ErrorKind;
+use super::ParseError;
+
+// Git escapes all bytes that are not printable ASCII characters when
+// "core.quotePath" is enabled, which is the default.
+fn needs_escape(byte: u8) -> bool {
+    !(b' '..=b'~').contains(&byte) || matches!(byte, b'"' | b'\\')
+}
+
+const fn sequence(byte: u8) -> Option<u8> {
+    Some(match byte {
+        b'\x07' => b'a',
+        b'\x08' => b'b',
+        b'\t' => b't',
+        b'\n' => b'n',
+        b'\x0B' => b'v',
+        b'\x0C' => b'f',
+        b'\r' => b'r',
+        b'"' | b'\\' => byte,
+        _ => return None,
+    })
+}
+
+pub(super) fn quote(bytes: &[u8], quote_spaces: bool, output: &mut Vec<u8>) {
+    if !bytes
+        .iter()
+        .any(|&x| needs_escape(x) || (quote_spaces && x == b' '))
+    {
+        output.extend_from_slice(bytes);
+        return;
+    }
+
+    output.push(b'"');
+    for &byte in bytes {
+        if let Some(sequence) = sequence(byte) {
+            output.push(b'\\');
+            output.push(sequence);
+        } else if needs_escape(byte) {
+            output.push(b'\\');
+            coreutils::push_octal(byte, output);
+        } else {
+            output.push(byte);
+        }
+    }
+    output.push(b'"');
+}
+
+/// Decodes a path quoted by Git.
+///
+/// Git quotes paths in its output using C-style escapes, such as `\n` and
+/// octal escapes for each byte that is not printable ASCII (e.g.,
+/// `"caf\303\251"`). Paths that do not require quoting are output as-is, so
+/// a string that does not begin with a quote is returned unchanged. This
+/// function accepts the same syntax as Git.
+///
+/// Paths can be quoted in the same format using [`QuotingStyle::Git`].
+///
+/// # Errors
+///
+/// Returns an error if the string begins with a quote but is not quoted
+/// correctly.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Quote;
+/// use uniquote::QuotingStyle;
+///
+/// let path = b"caf\xC3\xA9\n\x80";
+/// let quoted = path.quote().quoting_style(QuotingStyle::Git).to_string();
+/// assert_eq!(r#""caf\303\251\n\200""#, quoted);
+/// assert_eq!(Ok(&path[..]), uniquote::unquote_git(&quoted).as_deref());
+///
+/// assert_eq!(Ok(&b"a b"[..]), uniquote::unquote_git("a b").as_deref());
+/// ```
+///
+/// [`QuotingStyle::Git`]: super::QuotingStyle::Git
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+pub fn unquote_git(string: &str) -> Result<Vec<u8>, ParseError> {
+    let input = string.as_bytes();
+    let Some(quoted) = input.strip_prefix(b"\"") else {
+        return Ok(input.to_vec());
+    };
+
+    let error = |kind, offset| Err(ParseError::new(kind, input, offset));
+
+    let mut output = Vec::with_capacity(quoted.len());
+    let mut bytes =
+        quoted.iter().copied().enumerate().map(|(i, x)| (i + 1, x));
+    loop {
+        let Some((i, byte)) = bytes.next() else {
+            return error(ErrorKind::MissingQuote, input.len());
+        };
+        match byte {
+            b'"' => {
+                return if i + 1 == input.len() {
+                    Ok(output)
+                } else {
+                    error(ErrorKind::TrailingCharacters, i + 1)
+                };
+            }
+            b'\\' => {}
+            _ => {
+                output.push(byte);
+                continue;
+            }
+        }
+
+        let Some((i, byte)) = bytes.next() else {
+            return error(ErrorKind::UnterminatedEscape, input.len());
+        };
+        let byte = match byte {
+            b'a' => b'\x07',
+            b'b' => b'\x08',
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => b'\x0B',
+            b'f' => b'\x0C',
+            b'r' => b'\r',
+            b'"' | b'\\' => byte,
+            // Larger octal values would overflow a byte.
+            b'0'..=b'3' => {
+                let mut value = byte - b'0';
+                for _ in 0..2 {
+                    match bytes.next() {
+                        Some((_, digit @ b'0'..=b'7')) => {
+                            value = (value << 3) | (digit - b'0');
+                        }
+                        Some((i, _)) => {
+                            return error(ErrorKind::InvalidEscape, i);
+                        }
+                        None => {
+                            return error(
+                                ErrorKind::UnterminatedEscape,
+                                input.len(),
+                            );
+                        }
+                    }
+                }
+                value
+            }
+            _ => return error(ErrorKind::InvalidEscape, i),
+        };
+        output.push(byte);
+    }
+}
+
+#[cfg(feature = "os_str_bytes")]
+mod os_str {
+    use std::ffi::OsString;
+
+    use os_str_bytes::OsStringBytes;
+
+    use super::super::unquote::ErrorKind;
+    use super::super::ParseError;
+
+    /// Decodes a path quoted by Git into an [`OsString`].
+    ///
+    /// This function is equivalent to [`unquote_git`], but the path is
+    /// converted using [`OsStringBytes::from_io_vec`]. Git uses the same
+    /// encoding for paths on each platform.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not quoted correctly, or if the
+    /// decoded bytes cannot be represented on this platform.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ffi::OsStr;
+    ///
+    /// assert_eq!(
+    ///     Ok(OsStr::new("café")),
+    ///     uniquote::unquote_git_os_string(r#""caf\303\251""#).as_deref(),
+    /// );
+    /// ```
+    ///
+    /// [`unquote_git`]: super::unquote_git
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+    pub fn unquote_git_os_string(
+        string: &str,
+    ) -> Result<OsString, ParseError> {
+        let bytes = super::unquote_git(string)?;
+        OsString::from_io_vec(bytes).ok_or_else(|| {
+            ParseError::new(ErrorKind::InvalidEncoding, string.as_bytes(), 0)
+        })
+    }
+}
+#[cfg(feature = "os_str_bytes")]
+pub use os_str::unquote_git_os_string;
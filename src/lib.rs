@@ -61,6 +61,28 @@
 //!
 //! ### Optional Features
 //!
+//! - **clap** -
+//!   Provides [`QuotedArgParser`], which allows quoted command line arguments
+//!   to be parsed by [`clap`]. This feature also enables the
+//!   **os\_str\_bytes** feature.
+//!
+//! - **color** -
+//!   Provides [`Colors`], which allows escapes in quoted strings to be
+//!   highlighted using ANSI escape sequences.
+//!
+//! - **derive** -
+//!   Provides [a derive macro][macro@Quote] for [`Quote`], which can
+//!   implement it for structs and enums.
+//!
+//! - **log** -
+//!   Implements [`ToValue`] for the result of [`Quote::quote`], so that
+//!   quoted values can be used as key-values in [`log`] records.
+//!
+//! - **macros** -
+//!   Provides [`qformat!`] and similar macros, which quote arguments
+//!   formatted using the `q` format spec. Only [`qwrite!`] and
+//!   [`qwriteln!`] are available without the **std** feature.
+//!
 //! - **os\_str\_bytes** -
 //!   Provides implementations of [`Quote`] for:
 //!   - [`OsStr`]
@@ -72,6 +94,17 @@
 //!   - [`os_str_bytes::RawOsStr`]
 //!   - [`os_str_bytes::RawOsString`]
 //!
+//!   It also provides [`parse_quoted_arg`], [`PathError`], [`QuotedArgs`],
+//!   [`QuotedCommand`], and [`QuotedEnv`].
+//!
+//! - **serde** -
+//!   Provides [the `serde` module][mod@serde], which allows values to be
+//!   serialized as quoted strings.
+//!
+//! - **tracing** -
+//!   Provides [the `field` module][field], which allows values to be quoted
+//!   when recorded as fields by [`tracing`].
+//!
 //! # Examples
 //!
 //! **Printing Command Line Arguments:**
@@ -116,6 +149,7 @@
 //! [control characters]: char::is_ascii_control
 //! [`Debug`]: ::std::fmt::Debug
 //! [`Display`]: ::std::fmt::Display
+//! [`log`]: ::log
 //! [`OsStr`]: ::std::ffi::OsStr
 //! [`OsString`]: ::std::ffi::OsString
 //! [`Path`]: ::std::path::Path
@@ -123,6 +157,7 @@
 //! [`Path::to_string_lossy`]: ::std::path::Path::to_string_lossy
 //! [`PathBuf`]: ::std::path::PathBuf
 //! [`REPLACEMENT_CHARACTER`]: char::REPLACEMENT_CHARACTER
+//! [`ToValue`]: ::log::kv::ToValue
 
 // Only require a nightly compiler when building documentation for docs.rs.
 // This is a private option that should not be used.
@@ -134,18 +169,130 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "os_str_bytes")]
+mod arg;
+#[cfg(feature = "os_str_bytes")]
+pub use arg::parse_quoted_arg;
+#[cfg(feature = "clap")]
+pub use arg::QuotedArgParser;
+
+#[cfg(feature = "alloc")]
+mod binary;
+#[cfg(feature = "alloc")]
+pub use binary::Binary;
+
+#[cfg(feature = "color")]
+mod color;
+#[cfg(feature = "color")]
+pub use color::Colors;
+
+#[cfg(feature = "os_str_bytes")]
+mod command;
+#[cfg(feature = "os_str_bytes")]
+pub use command::QuotedArgs;
+#[cfg(feature = "os_str_bytes")]
+pub use command::QuotedCommand;
+#[cfg(feature = "os_str_bytes")]
+pub use command::QuotedEnv;
+
+#[cfg(feature = "alloc")]
+mod coreutils;
+#[cfg(feature = "alloc")]
+pub use coreutils::QuotingStyle;
+
+#[cfg(feature = "alloc")]
+mod debug;
+#[cfg(feature = "alloc")]
+pub use debug::unquote_debug;
+#[cfg(feature = "os_str_bytes")]
+pub use debug::unquote_debug_os_string;
+#[cfg(feature = "alloc")]
+pub use debug::DebugString;
+
+#[cfg(feature = "derive")]
+mod derive;
+#[cfg(feature = "derive")]
+pub use derive::Quote;
+
 mod escape;
+pub use escape::EscapeKind;
+pub use escape::Segment;
+pub use escape::Segments;
+pub use escape::StreamEscaper;
+
+#[cfg(feature = "tracing")]
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "tracing")))]
+pub mod field;
 
 mod formatter;
 pub use formatter::Error;
 pub use formatter::Formatter;
 pub use formatter::Result;
 
+#[cfg(feature = "alloc")]
+mod git;
+#[cfg(feature = "alloc")]
+pub use git::unquote_git;
+#[cfg(feature = "os_str_bytes")]
+pub use git::unquote_git_os_string;
+
+#[cfg(feature = "log")]
+mod log;
+
+#[cfg(feature = "macros")]
+mod macros;
+#[cfg(all(feature = "macros", feature = "std"))]
+pub use macros::qeprint;
+#[cfg(all(feature = "macros", feature = "std"))]
+pub use macros::qeprintln;
+#[cfg(all(feature = "macros", feature = "std"))]
+pub use macros::qformat;
+#[cfg(all(feature = "macros", feature = "std"))]
+pub use macros::qprint;
+#[cfg(all(feature = "macros", feature = "std"))]
+pub use macros::qprintln;
+#[cfg(feature = "macros")]
+pub use macros::qwrite;
+#[cfg(feature = "macros")]
+pub use macros::qwriteln;
+
+mod notation;
+pub use notation::Notation;
+
+#[cfg(feature = "os_str_bytes")]
+mod path;
+#[cfg(feature = "os_str_bytes")]
+pub use path::IoResultExt;
+#[cfg(feature = "os_str_bytes")]
+pub use path::PathError;
+
 mod quote;
 pub use quote::Quote;
 
+#[cfg(feature = "serde")]
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "serde")))]
+pub mod serde;
+
+#[cfg(feature = "alloc")]
+mod unquote;
+#[cfg(feature = "alloc")]
+pub use unquote::unquote;
+#[cfg(feature = "alloc")]
+pub use unquote::ParseError;
+#[cfg(feature = "std")]
+pub use unquote::UnquotingReader;
+
+mod writer;
+pub use writer::Overflow;
+#[cfg(feature = "std")]
+pub use writer::QuotingWriter;
+
 const QUOTE: char = '"';
 
 const START_ESCAPE: char = '{';
 
 const END_ESCAPE: char = '}';
+
+// The largest number of repetitions in a compact escape. It limits the
+// output of decoding, since the input may be untrusted.
+const MAX_REPEAT: usize = 65_535;
@@ -0,0 +1,15 @@
+use log::kv::ToValue;
+use log::kv::Value;
+
+use super::quote::Display;
+use super::Quote;
+
+impl<T> ToValue for Display<'_, T>
+where
+    T: Quote + ?Sized,
+{
+    #[inline]
+    fn to_value(&self) -> Value<'_> {
+        Value::from_display(self)
+    }
+}
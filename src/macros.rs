@@ -0,0 +1,102 @@
+/// Creates a [`String`] like [`format!`], with support for quoting
+/// arguments.
+///
+/// This macro accepts the same arguments as [`format!`]. Additionally, when
+/// the format spec of a placeholder is `q` (e.g., `{:q}`, `{0:q}`, or
+/// `{path:q}`), its argument is formatted using [`Quote::quote`]. Such
+/// arguments must implement [`Quote`], which is checked at compile time. The
+/// `q` spec cannot be combined with other formatting options.
+///
+/// The expanded code refers to this crate as `uniquote`, so it must not be
+/// renamed in "Cargo.toml" when this macro is used.
+///
+/// # Examples
+///
+/// ```
+/// let name = "new\nfile";
+/// assert_eq!(
+///     r#"opened "new{~n}file" (8 bytes)"#,
+///     uniquote::qformat!("opened {name:q} ({} bytes)", name.len()),
+/// );
+/// assert_eq!(
+///     r#""a\tb" is displayed as "a{~t}b""#,
+///     uniquote::qformat!("{0:?} is displayed as {0:q}", "a\tb"),
+/// );
+/// ```
+///
+/// Arguments that do not implement [`Quote`] cannot be quoted:
+///
+/// ```compile_fail
+/// let _ = uniquote::qformat!("{:q}", 1);
+/// ```
+///
+/// [`format!`]: ::std::format
+/// [`Quote`]: super::Quote
+/// [`Quote::quote`]: super::Quote::quote
+/// [`String`]: ::std::string::String
+#[cfg(feature = "std")]
+#[cfg_attr(
+    uniquote_docs_rs,
+    doc(cfg(all(feature = "macros", feature = "std")))
+)]
+pub use uniquote_macros::qformat;
+
+macro_rules! r#macro {
+    (
+        $(
+            $(#[ $attr:meta ])*
+            $name:ident => $std_name:ident , $description:literal ,
+        )+
+    ) => {
+    $(
+        #[doc = concat!(
+            $description,
+            " like [`",
+            stringify!($std_name),
+            "!`], with support for quoting arguments.",
+        )]
+        ///
+        /// See [`qformat!`] for the accepted syntax.
+        ///
+        #[doc = concat!(
+            "[`",
+            stringify!($std_name),
+            "!`]: ::std::",
+            stringify!($std_name),
+        )]
+        $(#[$attr])*
+        pub use uniquote_macros::$name;
+    )+
+    };
+}
+// The macros that expand to macros from the standard library require it.
+r#macro!(
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        uniquote_docs_rs,
+        doc(cfg(all(feature = "macros", feature = "std")))
+    )]
+    qeprint => eprint, "Prints to the standard error",
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        uniquote_docs_rs,
+        doc(cfg(all(feature = "macros", feature = "std")))
+    )]
+    qeprintln => eprintln, "Prints to the standard error, with a newline,",
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        uniquote_docs_rs,
+        doc(cfg(all(feature = "macros", feature = "std")))
+    )]
+    qprint => print, "Prints to the standard output",
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        uniquote_docs_rs,
+        doc(cfg(all(feature = "macros", feature = "std")))
+    )]
+    qprintln => println, "Prints to the standard output, with a newline,",
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "macros")))]
+    qwrite => write, "Writes formatted data into a buffer",
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "macros")))]
+    qwriteln => writeln, "Writes formatted data into a buffer, with a newline,",
+);
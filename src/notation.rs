@@ -0,0 +1,235 @@
+use core::fmt;
+
+use super::EscapeKind;
+use super::END_ESCAPE;
+use super::QUOTE;
+use super::START_ESCAPE;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Style {
+    Braces,
+    Rust,
+    Unicode,
+}
+
+/// The notation used to write escaped bytes, OS units, and code points.
+///
+/// Only the default notation is described in [the module-level
+/// documentation][format], and it is the only one that can be decoded by
+/// [`unquote`]. The others are intended for output that must match a
+/// convention used elsewhere. Escaped quotes and braces are written the same
+/// way in every notation.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Notation;
+/// use uniquote::Quote;
+///
+/// let string = "a\u{200B}\x1B\n";
+/// assert_eq!(
+///     r#""a{~u200b}{~u1b}{~n}""#,
+///     string.quote().notation(Notation::braces()).to_string(),
+/// );
+/// assert_eq!(
+///     r#""a\u{200b}\u{1b}\n""#,
+///     string.quote().notation(Notation::rust()).to_string(),
+/// );
+/// assert_eq!(
+///     r#""a<U+200B>^[^J""#,
+///     string
+///         .quote()
+///         .notation(Notation::unicode().caret(true))
+///         .to_string(),
+/// );
+/// ```
+///
+/// [format]: super#format
+/// [`unquote`]: super::unquote()
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Notation {
+    style: Style,
+    caret: bool,
+    uppercase: bool,
+}
+
+impl Notation {
+    const fn new(style: Style) -> Self {
+        Self {
+            style,
+            caret: false,
+            uppercase: false,
+        }
+    }
+
+    /// The default notation, which uses curly braces (e.g., `{~u200b}`,
+    /// `{~x80}`, and `{~n}`).
+    #[inline]
+    #[must_use]
+    pub const fn braces() -> Self {
+        Self::new(Style::Braces)
+    }
+
+    /// The notation used by Rust string literals (e.g., `\u{200b}`, `\x80`,
+    /// and `\n`).
+    ///
+    /// Since backslashes are never escaped, output using this notation may be
+    /// ambiguous.
+    #[inline]
+    #[must_use]
+    pub const fn rust() -> Self {
+        Self::new(Style::Rust)
+    }
+
+    /// The notation used by the Unicode Standard (e.g., `<U+200B>`), with at
+    /// least four digits.
+    ///
+    /// Bytes are written as two hexadecimal digits (e.g., `<0x80>`). Control
+    /// characters with short sequences in other notations are written as code
+    /// points. Uppercase digits are used by default.
+    #[inline]
+    #[must_use]
+    pub const fn unicode() -> Self {
+        Self::new(Style::Unicode).uppercase(true)
+    }
+
+    /// Writes C0 control characters and DEL using caret notation (e.g., `^@`
+    /// and `^[`), as done by `cat -v`.
+    ///
+    /// Since carets are never escaped, output using this option may be
+    /// ambiguous.
+    ///
+    /// This option is disabled by default.
+    #[inline]
+    #[must_use]
+    pub const fn caret(mut self, value: bool) -> Self {
+        self.caret = value;
+        self
+    }
+
+    /// Writes hexadecimal digits in uppercase.
+    ///
+    /// This option only affects digits. Prefixes such as `x` and `U+` are
+    /// unchanged.
+    #[inline]
+    #[must_use]
+    pub const fn uppercase(mut self, value: bool) -> Self {
+        self.uppercase = value;
+        self
+    }
+
+    fn caret_char(self, kind: EscapeKind) -> Option<char> {
+        if !self.caret {
+            return None;
+        }
+        let code_point = match kind {
+            EscapeKind::CodePoint(code_point) => code_point,
+            EscapeKind::Sequence(ch) => ch.into(),
+            _ => return None,
+        };
+        match code_point {
+            0x00..=0x1F => Some(char::from(code_point as u8 ^ 0x40)),
+            0x7F => Some('?'),
+            _ => None,
+        }
+    }
+
+    // Returns the prefix and value of an escape that can be grouped with
+    // adjacent escapes.
+    pub(super) fn unit(self, kind: EscapeKind) -> Option<(char, u64)> {
+        if self.style != Style::Braces || self.caret_char(kind).is_some() {
+            return None;
+        }
+        kind.unit()
+    }
+
+    pub(super) fn write_hex(
+        self,
+        value: u64,
+        min_digits: usize,
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        if self.uppercase {
+            write!(f, "{:01$X}", value, min_digits)
+        } else {
+            write!(f, "{:01$x}", value, min_digits)
+        }
+    }
+
+    pub(super) fn write(
+        self,
+        kind: EscapeKind,
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        if let Some(ch) = self.caret_char(kind) {
+            f.write_char('^')?;
+            return f.write_char(ch);
+        }
+
+        let (prefix, value) = match (self.style, kind) {
+            (_, EscapeKind::Brace(ch)) => {
+                f.write_char(ch)?;
+                return f.write_char(ch);
+            }
+            (_, EscapeKind::Quote) => {
+                f.write_char(START_ESCAPE)?;
+                f.write_char(QUOTE)?;
+                return f.write_char(END_ESCAPE);
+            }
+            (Style::Unicode, EscapeKind::Sequence(ch)) => {
+                ('u', u32::from(ch).into())
+            }
+            _ => {
+                if let Some(unit) = kind.unit() {
+                    unit
+                } else {
+                    let sequence =
+                        kind.sequence().expect("missing escape sequence");
+                    return if self.style == Style::Rust {
+                        write!(f, "\\{}", sequence)
+                    } else {
+                        write!(
+                            f,
+                            "{}~{}{}",
+                            START_ESCAPE, sequence, END_ESCAPE,
+                        )
+                    };
+                }
+            }
+        };
+
+        match (self.style, prefix) {
+            (Style::Braces, _) => {
+                write!(f, "{}~{}", START_ESCAPE, prefix)?;
+                self.write_hex(value, 1, f)?;
+                f.write_char(END_ESCAPE)
+            }
+            (Style::Rust, 'x') => {
+                f.write_str("\\x")?;
+                self.write_hex(value, 2, f)
+            }
+            (Style::Rust, _) => {
+                f.write_str("\\u{")?;
+                self.write_hex(value, 1, f)?;
+                f.write_char('}')
+            }
+            (Style::Unicode, 'x') => {
+                f.write_str("<0x")?;
+                self.write_hex(value, 2, f)?;
+                f.write_char('>')
+            }
+            (Style::Unicode, _) => {
+                f.write_str("<U+")?;
+                self.write_hex(value, 4, f)?;
+                f.write_char('>')
+            }
+        }
+    }
+}
+
+impl Default for Notation {
+    #[inline]
+    fn default() -> Self {
+        Self::braces()
+    }
+}
@@ -0,0 +1,143 @@
+use std::error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::Quote;
+
+/// An I/O error associated with a path.
+///
+/// The path is displayed using [`Quote::quote`], and the I/O error is
+/// returned by [`Error::source`]. This error can be created using
+/// [`IoResultExt::with_path`].
+///
+/// This type requires the **os_str_bytes** feature, since that feature
+/// provides the implementation of [`Quote`] for paths.
+///
+/// # Examples
+///
+/// ```
+/// use std::error::Error;
+/// use std::fs::File;
+/// use std::io;
+///
+/// use uniquote::IoResultExt;
+///
+/// let error = File::open("missing\nfile").with_path("missing\nfile");
+/// let error = error.unwrap_err();
+/// assert_eq!(
+///     r#"I/O error for path "missing{~n}file""#,
+///     error.to_string(),
+/// );
+/// assert_eq!(io::ErrorKind::NotFound, error.kind());
+/// assert!(error.source().is_some());
+/// ```
+///
+/// [`Error::source`]: error::Error::source
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+#[derive(Debug)]
+pub struct PathError {
+    path: PathBuf,
+    error: io::Error,
+}
+
+impl PathError {
+    /// Creates a new error for the given path.
+    #[inline]
+    #[must_use]
+    pub fn new<P>(path: P, error: io::Error) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            error,
+        }
+    }
+
+    /// Returns the path associated with this error.
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the I/O error contained by this error.
+    #[inline]
+    #[must_use]
+    pub fn io_error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the kind of the I/O error.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> io::ErrorKind {
+        self.error.kind()
+    }
+
+    /// Returns the path and I/O error contained by this error.
+    #[inline]
+    #[must_use]
+    pub fn into_parts(self) -> (PathBuf, io::Error) {
+        (self.path, self.error)
+    }
+}
+
+impl Display for PathError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "I/O error for path {}", self.path.quote())
+    }
+}
+
+impl error::Error for PathError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<PathError> for io::Error {
+    #[inline]
+    fn from(value: PathError) -> Self {
+        Self::new(value.kind(), value)
+    }
+}
+
+/// Extensions to [`io::Result`] for attaching paths to errors.
+///
+/// This trait is sealed and cannot be implemented for types outside of this
+/// crate.
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+pub trait IoResultExt<T>: private::Sealed {
+    /// Converts the error of this result to a [`PathError`] with the given
+    /// path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this result is an error.
+    fn with_path<P>(self, path: P) -> Result<T, PathError>
+    where
+        P: AsRef<Path>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    #[inline]
+    fn with_path<P>(self, path: P) -> Result<T, PathError>
+    where
+        P: AsRef<Path>,
+    {
+        self.map_err(|error| PathError::new(path.as_ref(), error))
+    }
+}
+
+mod private {
+    use std::io;
+
+    pub trait Sealed {}
+    impl<T> Sealed for io::Result<T> {}
+}
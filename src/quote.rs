@@ -1,28 +1,226 @@
 use core::ffi::CStr;
 use core::fmt;
-use core::fmt::Write as _;
+use core::result;
+
+#[cfg(feature = "alloc")]
+use ::alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use ::alloc::string::String;
 
 #[cfg(feature = "os_str_bytes")]
 use os_str_bytes::OsUnit;
 
+use super::formatter::Options;
+use super::writer::Counter;
+use super::writer::EscapeDetector;
+#[cfg(feature = "std")]
+use super::writer::IoWriter;
+use super::writer::SliceWriter;
+#[cfg(feature = "alloc")]
+use super::Binary;
+#[cfg(feature = "color")]
+use super::Colors;
 use super::Error;
 use super::Formatter;
+use super::Notation;
+use super::Overflow;
+#[cfg(feature = "alloc")]
+use super::QuotingStyle;
 use super::Result;
-use super::QUOTE;
 
 #[derive(Debug)]
-pub struct Display<T>(T);
+pub struct Display<'a, T>
+where
+    T: ?Sized,
+{
+    value: &'a T,
+    options: Options<'a>,
+}
 
-impl<T> fmt::Display for Display<&T>
+impl<'a, T> Display<'a, T>
 where
-    T: Quote + ?Sized,
+    T: ?Sized,
 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_char(QUOTE)?;
+    /// Displays the string across multiple lines.
+    ///
+    /// When enabled, line feeds and tabs are written literally instead of
+    /// being escaped. All other characters are escaped as usual. In
+    /// particular, carriage returns are always escaped, so `"\r\n"` is
+    /// displayed as `{~r}` followed by a line break, and a lone `"\r"` does
+    /// not start a new line.
+    ///
+    /// This option is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// assert_eq!(
+    ///     "\"foo{~r}\n\tbar{~r}baz\"",
+    ///     "foo\r\n\tbar\rbaz".quote().block(true).to_string(),
+    /// );
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn block(mut self, value: bool) -> Self {
+        self.options.literal_newlines = value;
+        self.options.literal_tabs = value;
+        self
+    }
+
+    /// Groups consecutive escapes of the same kind.
+    ///
+    /// When enabled, adjacent escaped bytes are written as a single escape
+    /// containing their values separated by spaces, and the same is done for
+    /// escaped code points. A value repeated multiple times is followed by
+    /// `*` and the number of repetitions in decimal. The number is at most
+    /// 65,535, so longer runs are split into multiple values. This syntax can
+    /// be decoded by [`unquote`]. It is only used with the default
+    /// [`Notation`].
+    ///
+    /// This option is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// assert_eq!(
+    ///     r#""{~x83 81 83 81}.txt""#,
+    ///     b"\x83\x81\x83\x81.txt".quote().compact(true).to_string(),
+    /// );
+    ///
+    /// let string = format!("a{}\u{1}", "\0".repeat(512));
+    /// assert_eq!(
+    ///     r#""a{~u0*512 1}""#,
+    ///     string.quote().compact(true).to_string(),
+    /// );
+    /// ```
+    ///
+    /// [`Notation`]: super::Notation
+    /// [`unquote`]: super::unquote()
+    #[inline]
+    #[must_use]
+    pub fn compact(mut self, value: bool) -> Self {
+        self.options.compact = value;
+        self
+    }
 
-        self.0.escape(Formatter::new(f)).map_err(|x| x.0)?;
+    /// Sets the notation used for escapes.
+    ///
+    /// By default, [`Notation::braces`] is used.
+    ///
+    /// [`Notation::braces`]: super::Notation::braces
+    #[inline]
+    #[must_use]
+    pub fn notation(mut self, notation: Notation) -> Self {
+        self.options.notation = notation;
+        self
+    }
 
-        f.write_char(QUOTE)
+    /// Formats the string using a quoting style from GNU coreutils.
+    ///
+    /// When a style is set, it replaces the format of this crate. All other
+    /// options are ignored, except for [`indent`] and [`line_prefix`]. See
+    /// [`QuotingStyle`] for more information.
+    ///
+    /// [`indent`]: Self::indent
+    /// [`line_prefix`]: Self::line_prefix
+    /// [`QuotingStyle`]: super::QuotingStyle
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+    #[inline]
+    #[must_use]
+    pub fn quoting_style(mut self, style: QuotingStyle) -> Self {
+        self.options.quoting_style = Some(style);
+        self
+    }
+
+    /// Sets the number of spaces written at the start of each line.
+    ///
+    /// Indentation is written before the first line and after each line
+    /// break displayed by [`block`]. It precedes any [`line_prefix`].
+    ///
+    /// [`block`]: Self::block
+    /// [`line_prefix`]: Self::line_prefix
+    #[inline]
+    #[must_use]
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.options.indent = indent;
+        self
+    }
+
+    /// Sets a string written at the start of each line.
+    ///
+    /// The prefix is written before the first line and after each line break
+    /// displayed by [`block`]. It is not escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// assert_eq!(
+    ///     "  | \"foo\n  | bar\"",
+    ///     "foo\nbar"
+    ///         .quote()
+    ///         .block(true)
+    ///         .indent(2)
+    ///         .line_prefix("| ")
+    ///         .to_string(),
+    /// );
+    /// ```
+    ///
+    /// [`block`]: Self::block
+    #[inline]
+    #[must_use]
+    pub fn line_prefix(mut self, prefix: &'a str) -> Self {
+        self.options.line_prefix = prefix;
+        self
+    }
+
+    /// Displays binary data using a compact representation.
+    ///
+    /// By default, all strings are quoted. See [`Binary`] for how binary
+    /// data is detected.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+    #[inline]
+    #[must_use]
+    pub fn binary(mut self, binary: Binary) -> Self {
+        self.options.binary = Some(binary);
+        self
+    }
+
+    /// Styles the output using ANSI escape sequences.
+    ///
+    /// By default, no styles are applied. Passing [`Colors::NONE`] also
+    /// disables styling, which is useful when output is not a terminal.
+    #[cfg(feature = "color")]
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "color")))]
+    #[inline]
+    #[must_use]
+    pub fn colors(mut self, colors: Colors) -> Self {
+        self.options.colors = colors;
+        self
+    }
+
+    // Escapes all invalid bytes, so that the output can be decoded exactly.
+    #[cfg(feature = "serde")]
+    pub(super) fn exact(mut self) -> Self {
+        self.options.exact = true;
+        self
+    }
+}
+
+impl<T> fmt::Display for Display<'_, T>
+where
+    T: Quote + ?Sized,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Formatter::with_options(f, self.options).write_quoted(self.value)
     }
 }
 
@@ -87,8 +285,217 @@ pub trait Quote {
     /// [format]: super#format
     #[inline]
     #[must_use]
-    fn quote(&self) -> Display<&Self> {
-        Display(self)
+    fn quote(&self) -> Display<'_, Self> {
+        Display {
+            value: self,
+            options: Options::new(),
+        }
+    }
+
+    /// Quotes a string directly into a [`fmt::Write`] sink.
+    ///
+    /// The result is identical to writing the value returned by [`quote`],
+    /// but no [`fmt::Formatter`] is created to do so.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if and only if writing to the sink fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// let mut string = String::new();
+    /// "foo\nbar".quote_to(&mut string)?;
+    /// assert_eq!(r#""foo{~n}bar""#, string);
+    /// #
+    /// # Ok::<_, std::fmt::Error>(())
+    /// ```
+    ///
+    /// [`quote`]: Self::quote
+    #[inline]
+    fn quote_to<W>(&self, mut writer: W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        Formatter::new(&mut writer).write_quoted(self)
+    }
+
+    /// Quotes a string directly into an [`io::Write`] sink.
+    ///
+    /// This method is equivalent to [`quote_to`], but the escaped bytes are
+    /// written to a byte stream.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if and only if writing to the sink fails. The error
+    /// returned by the sink will be propagated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// #
+    /// use uniquote::Quote;
+    ///
+    /// let mut buffer = Vec::new();
+    /// "foo\nbar".quote_to_io(&mut buffer)?;
+    /// assert_eq!(br#""foo{~n}bar""#, &*buffer);
+    /// #
+    /// # Ok::<_, io::Error>(())
+    /// ```
+    ///
+    /// [`io::Write`]: ::std::io::Write
+    /// [`quote_to`]: Self::quote_to
+    #[cfg(feature = "std")]
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "std")))]
+    #[inline]
+    fn quote_to_io<W>(&self, writer: W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let mut writer = IoWriter::new(writer);
+        let result = self.quote_to(&mut writer);
+        writer.finish(result)
+    }
+
+    /// Quotes a string into a fixed-size buffer.
+    ///
+    /// The returned string is a prefix of the buffer. This method is useful
+    /// when no allocator or output stream is available, since the buffer can
+    /// be allocated on the stack.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the buffer is shorter than the quoted string. The
+    /// contents of the buffer are unspecified in that case. Use [`quoted_len`]
+    /// to calculate the necessary length in advance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`escape`] returns an error for a reason other than the
+    /// buffer being full, which indicates that its implementation is
+    /// incorrect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// let mut buffer = [0; 16];
+    /// assert_eq!(Ok(r#""foo{~n}bar""#), "foo\nbar".quote_into(&mut buffer));
+    /// assert!("foo\nbar\nbaz".quote_into(&mut buffer).is_err());
+    /// ```
+    ///
+    /// [`escape`]: Self::escape
+    /// [`quoted_len`]: Self::quoted_len
+    #[inline]
+    fn quote_into<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> result::Result<&'a str, Overflow> {
+        let mut writer = SliceWriter::new(buffer);
+        let result = self.quote_to(&mut writer);
+        writer.finish(result)
+    }
+
+    /// Calculates the length of the quoted string in bytes.
+    ///
+    /// The result is exactly the length of the string that would be written
+    /// by [`quote_into`] or any other method of this trait.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`escape`] returns an error. Since counting bytes cannot
+    /// fail, this only occurs when the implementation of [`escape`] is
+    /// incorrect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// assert_eq!(12, "foo\nbar".quoted_len());
+    /// ```
+    ///
+    /// [`escape`]: Self::escape
+    /// [`quote_into`]: Self::quote_into
+    #[inline]
+    #[must_use]
+    fn quoted_len(&self) -> usize {
+        let mut writer = Counter(0);
+        self.quote_to(&mut writer)
+            .expect("a Quote implementation returned an error unexpectedly");
+        writer.0
+    }
+
+    /// Returns `true` if [`escape`] would modify this string.
+    ///
+    /// When this method returns `false`, the string is output verbatim between
+    /// the quotes. Scanning stops at the first character that must be escaped.
+    /// Invalid bytes are always considered to need escaping, even when they
+    /// would be displayed as printable characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uniquote::Quote;
+    ///
+    /// assert!(!"foo bar".needs_escaping());
+    /// assert!("foo\nbar".needs_escaping());
+    /// assert!(b"fo\x80o".needs_escaping());
+    /// assert!(b"caf\xE9".needs_escaping());
+    /// ```
+    ///
+    /// [`escape`]: Self::escape
+    #[inline]
+    #[must_use]
+    fn needs_escaping(&self) -> bool {
+        self.escape(&mut Formatter::with_segments(
+            &mut EscapeDetector,
+            Options::new(),
+        ))
+        .is_err()
+    }
+
+    /// Escapes a UTF-8 string, borrowing it when no escaping is necessary.
+    ///
+    /// The result is the same string written by [`escape`], so the
+    /// surrounding quotes are not included. No allocation occurs unless
+    /// [`needs_escaping`] returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use uniquote::Quote;
+    ///
+    /// assert!(matches!("foo bar".escape_cow(), Cow::Borrowed("foo bar")));
+    /// assert_eq!("foo{~n}bar", "foo\nbar".escape_cow());
+    /// ```
+    ///
+    /// [`escape`]: Self::escape
+    /// [`needs_escaping`]: Self::needs_escaping
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+    #[inline]
+    #[must_use]
+    fn escape_cow(&self) -> Cow<'_, str>
+    where
+        Self: AsRef<str>,
+    {
+        let string = self.as_ref();
+        if !string.needs_escaping() {
+            return Cow::Borrowed(string);
+        }
+
+        let mut escaped = String::with_capacity(string.len());
+        string
+            .escape(&mut Formatter::new(&mut escaped))
+            .expect("a Quote implementation returned an error unexpectedly");
+        Cow::Owned(escaped)
     }
 }
 
@@ -101,7 +508,7 @@ macro_rules! r#impl {
             fn escape(&self, f: &mut Formatter<'_>) -> $crate::Result {
                 use super::escape::Escape;
 
-                Escape::escape(self, &mut f.0).map_err(Error)
+                Escape::escape(self, f).map_err(Error)
             }
         }
     )+
@@ -0,0 +1,209 @@
+//! Modules for serializing values as quoted strings using [Serde].
+//!
+//! Each module can be used with the [`with`] attribute of Serde. Values are
+//! serialized as strings in the format of [`Quote::quote`], with all invalid
+//! bytes escaped, and deserialized using [`unquote`]. Therefore, values that
+//! cannot be represented as UTF-8 will be preserved, even by formats that
+//! only support strings.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "os_str_bytes")]
+//! # {
+//! use std::path::PathBuf;
+//!
+//! use serde::Deserialize;
+//! use serde::Serialize;
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Config {
+//!     #[serde(with = "uniquote::serde::path_buf")]
+//!     path: PathBuf,
+//!     #[serde(with = "uniquote::serde::bytes")]
+//!     key: Vec<u8>,
+//! }
+//!
+//! let config = Config {
+//!     path: PathBuf::from("data\n.bin"),
+//!     key: b"\xFFkey".to_vec(),
+//! };
+//! let json = serde_json::to_string(&config)?;
+//! assert_eq!(
+//!     r#"{"path":"\"data{~n}.bin\"","key":"\"{~xff}key\""}"#,
+//!     json,
+//! );
+//! assert_eq!(config, serde_json::from_str(&json)?);
+//! # }
+//! #
+//! # Ok::<_, serde_json::Error>(())
+//! ```
+//!
+//! [Serde]: ::serde
+//! [`Quote::quote`]: super::Quote::quote
+//! [`unquote`]: super::unquote()
+//! [`with`]: https://serde.rs/field-attrs.html#with
+
+use alloc::string::String;
+
+use ::serde::de::Error as _;
+use ::serde::Deserialize;
+use ::serde::Deserializer;
+use ::serde::Serializer;
+
+use super::ParseError;
+use super::Quote;
+
+fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Quote + ?Sized,
+    S: Serializer,
+{
+    serializer.collect_str(&value.quote().exact())
+}
+
+fn deserialize<'de, T, D, F>(
+    deserializer: D,
+    convert: F,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&str) -> Result<T, ParseError>,
+{
+    let string = String::deserialize(deserializer)?;
+    convert(&string).map_err(D::Error::custom)
+}
+
+/// Serializes byte strings, such as [`Vec<u8>`].
+pub mod bytes {
+    use alloc::vec::Vec;
+
+    use ::serde::Deserializer;
+    use ::serde::Serializer;
+
+    /// Serializes bytes as a quoted string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    #[inline]
+    pub fn serialize<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize(value, serializer)
+    }
+
+    /// Deserializes bytes from a quoted string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a string, or if it cannot be
+    /// decoded by [`unquote`].
+    ///
+    /// [`unquote`]: super::super::unquote()
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(deserializer, super::super::unquote)
+    }
+}
+
+/// Serializes platform strings, such as [`OsString`].
+///
+/// On Windows, strings containing unpaired surrogates are supported and
+/// round-trip losslessly.
+///
+/// [`OsString`]: ::std::ffi::OsString
+#[cfg(feature = "os_str_bytes")]
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+pub mod os_string {
+    use std::ffi::OsStr;
+    use std::ffi::OsString;
+
+    use ::serde::Deserializer;
+    use ::serde::Serializer;
+
+    /// Serializes a platform string as a quoted string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    #[inline]
+    pub fn serialize<S>(
+        value: &OsStr,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize(value, serializer)
+    }
+
+    /// Deserializes a platform string from a quoted string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a string, if it cannot be decoded
+    /// by [`unquote`], or if the decoded bytes cannot be represented on this
+    /// platform.
+    ///
+    /// [`unquote`]: super::super::unquote()
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OsString, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(
+            deserializer,
+            super::super::unquote::unquote_os_string,
+        )
+    }
+}
+
+/// Serializes paths, such as [`PathBuf`].
+///
+/// The same limitations apply as for [`os_string`].
+///
+/// [`PathBuf`]: ::std::path::PathBuf
+#[cfg(feature = "os_str_bytes")]
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "os_str_bytes")))]
+pub mod path_buf {
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use ::serde::Deserializer;
+    use ::serde::Serializer;
+
+    /// Serializes a path as a quoted string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    #[inline]
+    pub fn serialize<S>(value: &Path, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize(value, serializer)
+    }
+
+    /// Deserializes a path from a quoted string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a string, if it cannot be decoded
+    /// by [`unquote`], or if the decoded bytes cannot be represented on this
+    /// platform.
+    ///
+    /// [`unquote`]: super::super::unquote()
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<PathBuf, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::os_string::deserialize(deserializer).map(PathBuf::from)
+    }
+}
@@ -0,0 +1,772 @@
+use core::fmt;
+use core::fmt::Display;
+
+use alloc::vec::Vec;
+
+use super::formatter::Options;
+use super::formatter::SegmentWriter;
+use super::EscapeKind;
+use super::Formatter;
+use super::Quote;
+use super::Segment;
+use super::END_ESCAPE;
+use super::MAX_REPEAT;
+use super::QUOTE;
+use super::START_ESCAPE;
+
+const END: u8 = END_ESCAPE as u8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum ErrorKind {
+    InvalidCodePoint,
+    #[cfg(feature = "os_str_bytes")]
+    InvalidEncoding,
+    InvalidEscape,
+    MissingQuote,
+    RepeatCount,
+    TrailingCharacters,
+    UnescapedQuote,
+    UnterminatedEscape,
+}
+
+impl ErrorKind {
+    const fn message(self) -> &'static str {
+        match self {
+            Self::InvalidCodePoint => "escaped code point is not valid",
+            #[cfg(feature = "os_str_bytes")]
+            Self::InvalidEncoding => {
+                "decoded string is not valid on this platform"
+            }
+            Self::InvalidEscape => "invalid escape sequence",
+            Self::MissingQuote => "missing quote",
+            Self::RepeatCount => "repetition count is too large",
+            Self::TrailingCharacters => "unexpected characters after quote",
+            Self::UnescapedQuote => "unescaped quote",
+            Self::UnterminatedEscape => "unterminated escape sequence",
+        }
+    }
+}
+
+/// The error returned when decoding a quoted string fails.
+///
+/// The position of the error in the input is available from the methods of
+/// this struct.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    kind: ErrorKind,
+    offset: usize,
+    line: usize,
+}
+
+impl ParseError {
+    pub(super) fn new(kind: ErrorKind, input: &[u8], offset: usize) -> Self {
+        Self {
+            kind,
+            offset,
+            line: input[..offset].iter().filter(|&&x| x == b'\n').count() + 1,
+        }
+    }
+
+    /// Returns the byte offset of the error in the input.
+    ///
+    /// If the input ended unexpectedly, this value is the length of the
+    /// input.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the line number of the error in the input, starting at 1.
+    #[inline]
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl Display for ParseError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (line {}, offset {})",
+            self.kind.message(),
+            self.line,
+            self.offset,
+        )
+    }
+}
+
+struct Output<'a> {
+    bytes: &'a mut Vec<u8>,
+    // Whether escaped surrogates are allowed.
+    surrogates: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Escape {
+    Text,
+    Start,
+    End,
+    Quote,
+    Tilde,
+    Sequence(u8),
+    Hex {
+        is_byte: bool,
+        value: u32,
+        digits: u8,
+    },
+    Repeat {
+        is_byte: bool,
+        value: u32,
+        count: usize,
+    },
+}
+
+impl Escape {
+    fn push_unit(
+        is_byte: bool,
+        value: u32,
+        count: usize,
+        output: &mut Output<'_>,
+    ) -> Result<(), ErrorKind> {
+        let mut buffer = [0; 4];
+        let unit: &[u8] = if is_byte {
+            buffer[0] = value as u8;
+            &buffer[..1]
+        } else if let Some(ch) = char::from_u32(value) {
+            ch.encode_utf8(&mut buffer).as_bytes()
+        } else if output.surrogates && (0xD800..0xE000).contains(&value) {
+            // Surrogates are encoded in the same way as other code points,
+            // as in WTF-8.
+            buffer[..3].copy_from_slice(&[
+                0xED,
+                0x80 | ((value >> 6) & 0x3F) as u8,
+                0x80 | (value & 0x3F) as u8,
+            ]);
+            &buffer[..3]
+        } else {
+            return Err(ErrorKind::InvalidCodePoint);
+        };
+        for _ in 0..count {
+            output.bytes.extend_from_slice(unit);
+        }
+        Ok(())
+    }
+
+    // Units in compact escapes are separated by spaces and may be followed by
+    // a decimal number of repetitions (e.g., "{~x83 65*2}").
+    fn end_unit(
+        is_byte: bool,
+        value: u32,
+        count: usize,
+        byte: u8,
+        output: &mut Output<'_>,
+    ) -> Result<Self, ErrorKind> {
+        Self::push_unit(is_byte, value, count, output)?;
+        Ok(if byte == b' ' {
+            Self::Hex {
+                is_byte,
+                value: 0,
+                digits: 0,
+            }
+        } else {
+            Self::Text
+        })
+    }
+
+    fn push_hex(
+        is_byte: bool,
+        value: u32,
+        digits: u8,
+        byte: u8,
+        output: &mut Output<'_>,
+    ) -> Result<Self, ErrorKind> {
+        if digits != 0 {
+            match byte {
+                b' ' | END => {
+                    return Self::end_unit(is_byte, value, 1, byte, output);
+                }
+                b'*' => {
+                    return Ok(Self::Repeat {
+                        is_byte,
+                        value,
+                        count: 0,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let max_digits = if is_byte { 2 } else { 8 };
+        let digit = char::from(byte)
+            .to_digit(16)
+            .filter(|_| digits < max_digits)
+            .ok_or(ErrorKind::InvalidEscape)?;
+        Ok(Self::Hex {
+            is_byte,
+            value: (value << 4) | digit,
+            digits: digits + 1,
+        })
+    }
+
+    fn push_count(
+        is_byte: bool,
+        value: u32,
+        count: usize,
+        byte: u8,
+        output: &mut Output<'_>,
+    ) -> Result<Self, ErrorKind> {
+        if count != 0 && matches!(byte, b' ' | END) {
+            return Self::end_unit(is_byte, value, count, byte, output);
+        }
+
+        // Leading zeros are not allowed.
+        let digit = char::from(byte)
+            .to_digit(10)
+            .filter(|&x| count != 0 || x != 0)
+            .ok_or(ErrorKind::InvalidEscape)?;
+        let count = count * 10 + digit as usize;
+        if count > MAX_REPEAT {
+            return Err(ErrorKind::RepeatCount);
+        }
+        Ok(Self::Repeat {
+            is_byte,
+            value,
+            count,
+        })
+    }
+
+    fn push(
+        self,
+        byte: u8,
+        output: &mut Output<'_>,
+    ) -> Result<Self, ErrorKind> {
+        const START: u8 = START_ESCAPE as u8;
+        const QUOTE_BYTE: u8 = QUOTE as u8;
+
+        Ok(match (self, byte) {
+            (Self::Text, START) => Self::Start,
+            (Self::Text, END) => Self::End,
+            (Self::Text, QUOTE_BYTE) => return Err(ErrorKind::UnescapedQuote),
+            (Self::Text, _) => {
+                output.bytes.push(byte);
+                Self::Text
+            }
+
+            (Self::Start, START) | (Self::End, END) => {
+                output.bytes.push(byte);
+                Self::Text
+            }
+            (Self::Start, QUOTE_BYTE) => Self::Quote,
+            (Self::Start, b'~') => Self::Tilde,
+
+            (Self::Quote, END) => {
+                output.bytes.push(QUOTE_BYTE);
+                Self::Text
+            }
+
+            (Self::Tilde, b'n' | b'r' | b't') => Self::Sequence(byte),
+            (Self::Tilde, b'u' | b'x') => Self::Hex {
+                is_byte: byte == b'x',
+                value: 0,
+                digits: 0,
+            },
+
+            (Self::Sequence(sequence), END) => {
+                output.bytes.push(match sequence {
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    _ => unreachable!(),
+                });
+                Self::Text
+            }
+
+            (
+                Self::Hex {
+                    is_byte,
+                    value,
+                    digits,
+                },
+                _,
+            ) => return Self::push_hex(is_byte, value, digits, byte, output),
+            (
+                Self::Repeat {
+                    is_byte,
+                    value,
+                    count,
+                },
+                _,
+            ) => return Self::push_count(is_byte, value, count, byte, output),
+
+            _ => return Err(ErrorKind::InvalidEscape),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Quoted {
+    Start,
+    Inside,
+    End,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Parser {
+    escape: Escape,
+    // [None] indicates that the input is not quoted.
+    quoted: Option<Quoted>,
+    lines: bool,
+    surrogates: bool,
+    offset: usize,
+    line: usize,
+}
+
+impl Parser {
+    pub(super) fn new(quoted: bool, lines: bool) -> Self {
+        Self {
+            escape: Escape::Text,
+            quoted: quoted.then_some(Quoted::Start),
+            lines,
+            surrogates: false,
+            offset: 0,
+            line: 1,
+        }
+    }
+
+    fn error(&self, kind: ErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            offset: self.offset,
+            line: self.line,
+        }
+    }
+
+    fn push_quoted(
+        &mut self,
+        quoted: Quoted,
+        byte: u8,
+        output: &mut Output<'_>,
+    ) -> Result<Quoted, ErrorKind> {
+        const QUOTE_BYTE: u8 = QUOTE as u8;
+
+        Ok(match (quoted, byte) {
+            (Quoted::Start, QUOTE_BYTE) => Quoted::Inside,
+            (Quoted::Start, _) => return Err(ErrorKind::MissingQuote),
+
+            (Quoted::Inside, QUOTE_BYTE)
+                if matches!(self.escape, Escape::Text) =>
+            {
+                Quoted::End
+            }
+            (Quoted::Inside, b'\n') => return Err(ErrorKind::MissingQuote),
+            (Quoted::Inside, _) => {
+                self.escape = self.escape.push(byte, output)?;
+                Quoted::Inside
+            }
+
+            (Quoted::End, b'\n') if self.lines => {
+                output.bytes.push(byte);
+                Quoted::Start
+            }
+            (Quoted::End, _) => return Err(ErrorKind::TrailingCharacters),
+        })
+    }
+
+    pub(super) fn push(
+        &mut self,
+        byte: u8,
+        output: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
+        let mut output = Output {
+            bytes: output,
+            surrogates: self.surrogates,
+        };
+        let result = if let Some(quoted) = self.quoted {
+            self.push_quoted(quoted, byte, &mut output)
+                .map(|x| self.quoted = Some(x))
+        } else if byte == b'\n' && matches!(self.escape, Escape::Text) {
+            output.bytes.push(byte);
+            Ok(())
+        } else {
+            self.escape.push(byte, &mut output).map(|x| self.escape = x)
+        };
+        result.map_err(|x| self.error(x))?;
+
+        self.offset += 1;
+        if byte == b'\n' {
+            self.line += 1;
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(&self) -> Result<(), ParseError> {
+        let kind = match self.quoted {
+            Some(Quoted::Start) if self.lines => return Ok(()),
+            Some(Quoted::Start | Quoted::Inside) => ErrorKind::MissingQuote,
+            Some(Quoted::End) | None
+                if matches!(self.escape, Escape::Text) =>
+            {
+                return Ok(());
+            }
+            _ => ErrorKind::UnterminatedEscape,
+        };
+        Err(self.error(kind))
+    }
+}
+
+// Collects the bytes that escaped segments represent.
+pub(super) struct Decoder {
+    // [None] indicates that an escape cannot be represented as bytes.
+    bytes: Option<Vec<u8>>,
+}
+
+impl Decoder {
+    pub(super) fn new() -> Self {
+        Self {
+            bytes: Some(Vec::new()),
+        }
+    }
+
+    pub(super) fn finish(self) -> Option<Vec<u8>> {
+        self.bytes
+    }
+}
+
+impl SegmentWriter for Decoder {
+    fn write_segment(&mut self, segment: Segment<'_>) -> fmt::Result {
+        let Some(bytes) = &mut self.bytes else {
+            return Ok(());
+        };
+        let mut buffer = [0; 4];
+        let segment: &[u8] = match segment {
+            Segment::Literal(string) => string.as_bytes(),
+            Segment::Escape(kind) => match kind {
+                EscapeKind::Brace(ch) | EscapeKind::Sequence(ch) => {
+                    ch.encode_utf8(&mut buffer).as_bytes()
+                }
+                EscapeKind::Byte(byte) => {
+                    buffer[0] = byte;
+                    &buffer[..1]
+                }
+                EscapeKind::CodePoint(code_point) => {
+                    match char::from_u32(code_point) {
+                        Some(ch) => ch.encode_utf8(&mut buffer).as_bytes(),
+                        None => {
+                            self.bytes = None;
+                            return Ok(());
+                        }
+                    }
+                }
+                // Units that are not bytes are surrogates on Windows.
+                EscapeKind::OsUnit(unit) => match u8::try_from(unit) {
+                    Ok(byte) => {
+                        buffer[0] = byte;
+                        &buffer[..1]
+                    }
+                    Err(_) => {
+                        self.bytes = None;
+                        return Ok(());
+                    }
+                },
+                EscapeKind::Quote => &[QUOTE as u8],
+            },
+        };
+        bytes.extend_from_slice(segment);
+        Ok(())
+    }
+}
+
+// Returns the bytes that the value represents, or [None] if they cannot be
+// determined.
+pub(super) fn decode<T>(value: &T) -> Option<Vec<u8>>
+where
+    T: Quote + ?Sized,
+{
+    let mut decoder = Decoder::new();
+    value
+        .escape(&mut Formatter::with_segments(&mut decoder, Options::new()))
+        .ok()?;
+    decoder.finish()
+}
+
+/// Decodes a string quoted by [`Quote::quote`].
+///
+/// The string must begin and end with a quote. All escapes described in [the
+/// module-level documentation][format] are decoded, and the resulting bytes
+/// are returned.
+///
+/// Not all quoted strings can be decoded exactly. For example, invalid bytes
+/// may be displayed as printable characters, which will be decoded as UTF-8.
+/// However, the output of [`QuotingWriter`] can be decoded exactly when
+/// [`QuotingWriter::exact`] is enabled.
+///
+/// Grouped escapes, which are written when compact output is enabled, are
+/// also decoded. To limit the size of the output, a number of repetitions
+/// greater than 65,535 is rejected, as are numbers with leading zeros.
+///
+/// Escaped surrogates (e.g., `{~ud800}`) cannot be represented as bytes, so
+/// they are rejected. They are written when quoting an [`OsStr`] on Windows,
+/// but [`parse_quoted_arg`] can decode them.
+///
+/// # Errors
+///
+/// Returns an error if the string is not quoted or contains an invalid escape.
+///
+/// # Examples
+///
+/// ```
+/// use uniquote::Quote;
+///
+/// let string = b"fo\x80o\n{\"}";
+/// let quoted = string.quote().to_string();
+/// assert_eq!(r#""fo{~x80}o{~n}{{{"}}}""#, quoted);
+/// assert_eq!(Ok(&string[..]), uniquote::unquote(&quoted).as_deref());
+/// ```
+///
+/// [format]: super#format
+/// [`Quote::quote`]: super::Quote::quote
+/// [`OsStr`]: ::std::ffi::OsStr
+/// [`parse_quoted_arg`]: super::parse_quoted_arg
+/// [`QuotingWriter`]: super::QuotingWriter
+/// [`QuotingWriter::exact`]: super::QuotingWriter::exact
+#[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn unquote(string: &str) -> Result<Vec<u8>, ParseError> {
+    decode_quoted(string, false)
+}
+
+fn decode_quoted(
+    string: &str,
+    surrogates: bool,
+) -> Result<Vec<u8>, ParseError> {
+    let mut parser = Parser::new(true, false);
+    parser.surrogates = surrogates;
+    let mut output = Vec::with_capacity(string.len());
+    for &byte in string.as_bytes() {
+        parser.push(byte, &mut output)?;
+    }
+    parser.finish()?;
+    Ok(output)
+}
+
+// Decodes a quoted string into the platform string with the same bytes.
+// Escaped surrogates are accepted on Windows, where they can be represented.
+#[cfg(feature = "os_str_bytes")]
+pub(super) fn unquote_os_string(
+    string: &str,
+) -> Result<::std::ffi::OsString, ParseError> {
+    use ::std::ffi::OsString;
+
+    let error =
+        || ParseError::new(ErrorKind::InvalidEncoding, string.as_bytes(), 0);
+
+    #[cfg(windows)]
+    {
+        use ::std::os::windows::ffi::OsStringExt;
+        use ::std::str;
+
+        // Surrogates are decoded as in WTF-8.
+        let bytes = decode_quoted(string, true)?;
+        let mut bytes = &*bytes;
+        let mut wide = Vec::new();
+        loop {
+            let valid = match str::from_utf8(bytes) {
+                Ok(valid) => valid,
+                Err(error) => {
+                    let (valid, _) = bytes.split_at(error.valid_up_to());
+                    str::from_utf8(valid).expect("invalid UTF-8")
+                }
+            };
+            wide.extend(valid.encode_utf16());
+            bytes = &bytes[valid.len()..];
+            match *bytes {
+                [] => break,
+                [0xED, high @ 0xA0..=0xBF, low @ 0x80..=0xBF, ref rest @ ..] =>
+                {
+                    wide.push(
+                        0xD000
+                            | (u16::from(high & 0x3F) << 6)
+                            | u16::from(low & 0x3F),
+                    );
+                    bytes = rest;
+                }
+                _ => return Err(error()),
+            }
+        }
+        Ok(OsString::from_wide(&wide))
+    }
+    #[cfg(not(windows))]
+    {
+        use os_str_bytes::OsStringBytes;
+
+        OsString::from_io_vec(unquote(string)?).ok_or_else(error)
+    }
+}
+
+#[cfg(feature = "std")]
+mod std {
+    use std::error;
+    use std::io;
+    use std::io::Read;
+
+    use super::ParseError;
+    use super::Parser;
+
+    impl error::Error for ParseError {}
+
+    /// An [`io::Read`] adapter that decodes quoted strings.
+    ///
+    /// By default, the entire stream is expected to be escaped without
+    /// surrounding quotes, as written by [`QuotingWriter`]. Its output is
+    /// decoded byte for byte when [`QuotingWriter::exact`] is enabled.
+    /// Unescaped newlines are also allowed, for compatibility with
+    /// [`QuotingWriter::literal_newlines`]. As with [`unquote`], escaped
+    /// surrogates are rejected.
+    ///
+    /// Alternatively, [`lines`] can be enabled to read a stream with one
+    /// quoted string on each line. The quotes will be removed, and the
+    /// newlines between them will be preserved.
+    ///
+    /// When the stream cannot be decoded, [`read`] returns an error of kind
+    /// [`io::ErrorKind::InvalidData`], which contains a [`ParseError`] with
+    /// the position of the invalid input. All bytes decoded before that
+    /// position will have been returned by previous calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// # use std::io;
+    ///
+    /// use uniquote::UnquotingReader;
+    ///
+    /// let log = b"\"foo{~x80}\"\n\"{~u1b}[2J\"\n";
+    ///
+    /// let mut reader = UnquotingReader::new(&log[..]).lines(true);
+    /// let mut bytes = Vec::new();
+    /// let _ = reader.read_to_end(&mut bytes)?;
+    /// assert_eq!(b"foo\x80\n\x1B[2J\n", &*bytes);
+    /// #
+    /// # Ok::<_, io::Error>(())
+    /// ```
+    ///
+    /// [`lines`]: Self::lines
+    /// [`QuotingWriter`]: crate::QuotingWriter
+    /// [`QuotingWriter::exact`]: crate::QuotingWriter::exact
+    /// [`QuotingWriter::literal_newlines`]: crate::QuotingWriter::literal_newlines
+    /// [`unquote`]: crate::unquote()
+    /// [`read`]: io::Read::read
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "std")))]
+    #[derive(Debug)]
+    pub struct UnquotingReader<R> {
+        reader: R,
+        parser: Parser,
+        buffer: Vec<u8>,
+        index: usize,
+        error: Option<ParseError>,
+    }
+
+    impl<R> UnquotingReader<R>
+    where
+        R: Read,
+    {
+        /// Creates a new adapter that reads from the given stream.
+        #[inline]
+        #[must_use]
+        pub fn new(reader: R) -> Self {
+            Self {
+                reader,
+                parser: Parser::new(false, true),
+                buffer: Vec::new(),
+                index: 0,
+                error: None,
+            }
+        }
+
+        /// Sets whether each line of the stream is a quoted string.
+        ///
+        /// This option is disabled by default.
+        ///
+        /// # Panics
+        ///
+        /// Panics if any bytes have already been read.
+        #[inline]
+        #[must_use]
+        pub fn lines(mut self, value: bool) -> Self {
+            assert_eq!(0, self.parser.offset, "stream has already been read");
+            self.parser = Parser::new(value, true);
+            self
+        }
+
+        /// Returns a reference to the underlying stream.
+        #[inline]
+        #[must_use]
+        pub fn get_ref(&self) -> &R {
+            &self.reader
+        }
+
+        /// Returns the underlying stream.
+        ///
+        /// Any bytes decoded but not yet read will be discarded.
+        #[inline]
+        #[must_use]
+        pub fn into_inner(self) -> R {
+            self.reader
+        }
+
+        fn fill_buffer(&mut self) -> io::Result<()> {
+            let mut input = [0; 0x1000];
+            self.buffer.clear();
+            self.index = 0;
+            while self.buffer.is_empty() {
+                if let Some(error) = &self.error {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        error.clone(),
+                    ));
+                }
+
+                let length = self.reader.read(&mut input)?;
+                if length == 0 {
+                    self.error = self.parser.finish().err();
+                    if self.error.is_none() {
+                        break;
+                    }
+                    continue;
+                }
+
+                for &byte in &input[..length] {
+                    if let Err(error) =
+                        self.parser.push(byte, &mut self.buffer)
+                    {
+                        self.error = Some(error);
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<R> Read for UnquotingReader<R>
+    where
+        R: Read,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.index == self.buffer.len() {
+                self.fill_buffer()?;
+            }
+
+            let buffer = &self.buffer[self.index..];
+            let length = buffer.len().min(buf.len());
+            buf[..length].copy_from_slice(&buffer[..length]);
+            self.index += length;
+            Ok(length)
+        }
+    }
+}
+#[cfg(feature = "std")]
+pub use self::std::UnquotingReader;
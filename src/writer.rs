@@ -0,0 +1,294 @@
+use core::fmt;
+use core::fmt::Display;
+use core::fmt::Formatter;
+use core::str;
+
+use super::formatter::SegmentWriter;
+use super::Segment;
+
+/// The error returned by [`Quote::quote_into`].
+///
+/// This error indicates that the buffer provided was too small to contain the
+/// quoted string. The necessary length can be calculated using
+/// [`Quote::quoted_len`].
+///
+/// [`Quote::quote_into`]: super::Quote::quote_into
+/// [`Quote::quoted_len`]: super::Quote::quoted_len
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overflow(());
+
+impl Display for Overflow {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("buffer is too small for the quoted string")
+    }
+}
+
+pub(super) struct Counter(pub(super) usize);
+
+impl fmt::Write for Counter {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.0 += string.len();
+        Ok(())
+    }
+}
+
+// Stops formatting at the first escape.
+pub(super) struct EscapeDetector;
+
+impl SegmentWriter for EscapeDetector {
+    fn write_segment(&mut self, segment: Segment<'_>) -> fmt::Result {
+        match segment {
+            Segment::Escape(_) => Err(fmt::Error),
+            Segment::Literal(_) => Ok(()),
+        }
+    }
+}
+
+pub(super) struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub(super) fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    pub(super) fn finish(
+        self,
+        result: fmt::Result,
+    ) -> Result<&'a str, Overflow> {
+        match result {
+            Ok(()) => {
+                let buffer = &self.buffer[..self.len];
+                // SAFETY: Only complete strings are copied to the buffer.
+                Ok(unsafe { str::from_utf8_unchecked(buffer) })
+            }
+            Err(_) if self.overflowed => Err(Overflow(())),
+            Err(_) => {
+                panic!("a Quote implementation returned an error unexpectedly")
+            }
+        }
+    }
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        let Some(buffer) = self
+            .buffer
+            .get_mut(self.len..)
+            .and_then(|x| x.get_mut(..string.len()))
+        else {
+            self.overflowed = true;
+            return Err(fmt::Error);
+        };
+        buffer.copy_from_slice(string.as_bytes());
+        self.len += string.len();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use std::fmt;
+    use std::io;
+
+    use crate::StreamEscaper;
+
+    #[derive(Debug)]
+    pub(crate) struct IoWriter<W> {
+        writer: W,
+        error: Option<io::Error>,
+    }
+
+    impl<W> IoWriter<W>
+    where
+        W: io::Write,
+    {
+        pub(crate) fn new(writer: W) -> Self {
+            Self {
+                writer,
+                error: None,
+            }
+        }
+
+        fn take_error(&mut self) -> io::Error {
+            self.error
+                .take()
+                .unwrap_or_else(|| io::Error::other("formatter error"))
+        }
+
+        pub(crate) fn finish(mut self, result: fmt::Result) -> io::Result<()> {
+            result.map_err(|_| self.take_error())
+        }
+    }
+
+    impl<W> fmt::Write for IoWriter<W>
+    where
+        W: io::Write,
+    {
+        fn write_str(&mut self, string: &str) -> fmt::Result {
+            self.writer.write_all(string.as_bytes()).map_err(|error| {
+                self.error = Some(error);
+                fmt::Error
+            })
+        }
+    }
+
+    /// An [`io::Write`] adapter that escapes all bytes written to it.
+    ///
+    /// Each call to [`write`] is escaped using [`StreamEscaper`], so UTF-8
+    /// sequences split between calls are handled correctly. The result is
+    /// the same as escaping all bytes at once. Surrounding quotes are not
+    /// written. When [`exact`] is enabled, the output can be decoded exactly
+    /// by [`UnquotingReader`].
+    ///
+    /// Since bytes at the end of a call may be stored until the next call,
+    /// [`finish`] should be called after writing the last byte. Otherwise,
+    /// those bytes will be lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// # use std::io;
+    ///
+    /// use uniquote::QuotingWriter;
+    ///
+    /// let mut writer = QuotingWriter::new(Vec::new()).literal_newlines(true);
+    /// writer.write_all(b"foo\x1B[2J\n\xE2\x82")?;
+    /// writer.write_all(b"\xAC\r\n")?;
+    /// assert_eq!(b"foo{~u1b}[2J\n\xE2\x82\xAC{~r}\n", &*writer.finish()?);
+    /// #
+    /// # Ok::<_, io::Error>(())
+    /// ```
+    ///
+    /// [`exact`]: Self::exact
+    /// [`finish`]: Self::finish
+    /// [`UnquotingReader`]: crate::UnquotingReader
+    /// [`write`]: io::Write::write
+    #[cfg_attr(uniquote_docs_rs, doc(cfg(feature = "std")))]
+    #[derive(Debug)]
+    pub struct QuotingWriter<W>(StreamEscaper<IoWriter<W>>)
+    where
+        W: io::Write;
+
+    impl<W> QuotingWriter<W>
+    where
+        W: io::Write,
+    {
+        /// Creates a new adapter that writes to the given stream.
+        #[inline]
+        #[must_use]
+        pub fn new(writer: W) -> Self {
+            Self(StreamEscaper::new(IoWriter::new(writer)))
+        }
+
+        /// Sets whether invalid bytes will always be written as hexadecimal
+        /// escapes.
+        ///
+        /// By default, some invalid bytes are displayed as printable
+        /// characters, which cannot be distinguished from valid UTF-8 when
+        /// decoding. Enabling this option allows the output to be decoded
+        /// byte for byte by [`UnquotingReader`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use std::io::Write;
+        /// # use std::io;
+        ///
+        /// use uniquote::QuotingWriter;
+        ///
+        /// let mut writer = QuotingWriter::new(Vec::new()).exact(true);
+        /// writer.write_all(b"caf\xE9")?;
+        /// assert_eq!(b"caf{~xe9}", &*writer.finish()?);
+        /// #
+        /// # Ok::<_, io::Error>(())
+        /// ```
+        ///
+        /// [`UnquotingReader`]: crate::UnquotingReader
+        #[inline]
+        #[must_use]
+        pub fn exact(mut self, value: bool) -> Self {
+            self.0.options.exact = value;
+            self
+        }
+
+        /// Sets whether newlines (`\n`) will be written without escaping
+        /// them.
+        ///
+        /// This option is disabled by default. Enabling it allows the output
+        /// to be read line by line, but the resulting format is not described
+        /// by [the module-level documentation][format].
+        ///
+        /// [format]: crate#format
+        #[inline]
+        #[must_use]
+        pub fn literal_newlines(mut self, value: bool) -> Self {
+            self.0.options.literal_newlines = value;
+            self
+        }
+
+        /// Returns a reference to the underlying stream.
+        #[inline]
+        #[must_use]
+        pub fn get_ref(&self) -> &W {
+            &self.0.get_ref().writer
+        }
+
+        /// Escapes any bytes stored from the last call to [`write`] and
+        /// returns the underlying stream.
+        ///
+        /// # Errors
+        ///
+        /// This method fails if and only if writing to the underlying stream
+        /// fails.
+        ///
+        /// [`write`]: io::Write::write
+        #[inline]
+        pub fn finish(mut self) -> io::Result<W> {
+            if self.0.escape_incomplete().is_err() {
+                return Err(self.0.get_mut().take_error());
+            }
+            Ok(self.0.into_inner().writer)
+        }
+    }
+
+    impl<W> io::Write for QuotingWriter<W>
+    where
+        W: io::Write,
+    {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .feed(buf)
+                .map_err(|_| self.0.get_mut().take_error())?;
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.get_mut().writer.flush()
+        }
+    }
+}
+#[cfg(feature = "std")]
+pub(super) use io::IoWriter;
+#[cfg(feature = "std")]
+pub use io::QuotingWriter;
+
+#[cfg(feature = "std")]
+mod std {
+    use std::error;
+
+    use super::Overflow;
+
+    impl error::Error for Overflow {}
+}
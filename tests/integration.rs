@@ -90,3 +90,1303 @@ fn test_os_string() {
         );
     }
 }
+
+#[test]
+fn test_writers() {
+    let mut string = String::new();
+    "foo\"bar".quote_to(&mut string).unwrap();
+    assert_eq!(r#""foo{"}bar""#, string);
+
+    #[cfg(feature = "std")]
+    {
+        let mut buffer = Vec::new();
+        b"fo\x80o".quote_to_io(&mut buffer).unwrap();
+        assert_eq!(br#""fo{~x80}o""#, &*buffer);
+    }
+}
+
+#[test]
+fn test_buffer() {
+    let string = "foo\u{200B}bar";
+    let quoted = r#""foo{~u200b}bar""#;
+    assert_eq!(quoted.len(), string.quoted_len());
+
+    let mut buffer = [0; 32];
+    assert_eq!(Ok(quoted), string.quote_into(&mut buffer));
+    assert_eq!(Ok(quoted), string.quote_into(&mut buffer[..quoted.len()]));
+    assert!(string.quote_into(&mut buffer[..quoted.len() - 1]).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_escape_cow() {
+    use std::borrow::Cow;
+
+    assert!(matches!("系统".escape_cow(), Cow::Borrowed("系统")));
+    assert!(matches!(
+        String::from("a\\b").escape_cow(),
+        Cow::Borrowed("a\\b"),
+    ));
+    assert_eq!("{{a}}", "{a}".escape_cow());
+    assert_eq!("a}}b", "a}b".escape_cow());
+    assert_eq!("a}}}}b", "a}}b".escape_cow());
+
+    assert!(!'a'.needs_escaping());
+    assert!('{'.needs_escaping());
+    assert!('}'.needs_escaping());
+    assert!("a}b".needs_escaping());
+    assert!("a}}b".needs_escaping());
+    assert!("ab\u{200B}".needs_escaping());
+    assert!(!b"abc".needs_escaping());
+    assert!(b"caf\xe9".needs_escaping());
+    assert!(b"\xA0".needs_escaping());
+    assert!(b"\xFF".needs_escaping());
+}
+
+#[test]
+fn test_segments() {
+    use uniquote::EscapeKind;
+    use uniquote::Segment;
+    use uniquote::Segments;
+
+    fn test_all(bytes: &[u8]) {
+        let segments: String =
+            Segments::from(bytes).map(|x| x.to_string()).collect();
+        test(&format!(r#""{}""#, segments), bytes.quote());
+    }
+
+    test_all(&(0..=u8::MAX).collect::<Vec<_>>());
+    test_all("a{b}\"c\u{200B}d\r\n".as_bytes());
+
+    assert_eq!(
+        [
+            Segment::Literal("a"),
+            Segment::Escape(EscapeKind::Brace('{')),
+            Segment::Literal("é"),
+            Segment::Escape(EscapeKind::Quote),
+        ],
+        *Segments::from("a{é\"").collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn test_block() {
+    test(
+        "\"a\n\tb{~r}\nc{~r}d\"",
+        "a\n\tb\r\nc\rd".quote().block(true),
+    );
+    test(r#""a{~n}b""#, "a\nb".quote().block(false));
+    test(
+        "# \"\n# {~u0}\n# \"",
+        b"\n\0\n".quote().block(true).line_prefix("# "),
+    );
+    test("   \"a{~n}\"", "a\n".quote().indent(3));
+
+    let prefix = format!("[{}] ", 7);
+    test(
+        "[7] \"a\n[7] b\"",
+        "a\nb".quote().block(true).line_prefix(&prefix),
+    );
+}
+
+#[test]
+fn test_compact() {
+    test(
+        r#""{~x80 81*2}a{~x80}{~u0*3 200b}{~n}{~u7f}{"}""#,
+        b"\x80\x81\x81a\x80\0\0\0\xE2\x80\x8B\n\x7F\""
+            .quote()
+            .compact(true),
+    );
+    test(r#""{~x80}{~x80}""#, b"\x80\x80".quote().compact(false));
+
+    #[cfg(feature = "alloc")]
+    for bytes in [
+        &b"\x80\x81\x81a\x80\0\0\0\xE2\x80\x8B\x7F"[..],
+        &[0x9F; 1000],
+        &[0x9F; 70_000],
+        "\u{200B}\u{200C}\u{200C}{\u{7F}}".as_bytes(),
+    ] {
+        let quoted = bytes.quote().compact(true).to_string();
+        assert_eq!(Ok(bytes), uniquote::unquote(&quoted).as_deref());
+    }
+    #[cfg(feature = "alloc")]
+    for quoted in [
+        r#""{~x8 }""#,
+        r#""{~x8*}""#,
+        r#""{~x8*0}""#,
+        r#""{~x8*02}""#,
+        r#""{~u*2}""#,
+        r#""{~u0*65536}""#,
+        r#""{~u0*99999999999999}""#,
+    ] {
+        assert!(uniquote::unquote(quoted).is_err());
+    }
+    #[cfg(feature = "alloc")]
+    assert_eq!(Ok(vec![0; 65_535]), uniquote::unquote(r#""{~u0*65535}""#),);
+    test(
+        r#""{~x9f*65535 9f*2}""#,
+        [0x9F; 65_537].quote().compact(true),
+    );
+}
+
+#[test]
+fn test_notation() {
+    use uniquote::Notation;
+
+    let bytes = b"\0\x1B\x7F\x80\xC2\x9F\t{\"";
+    for (expected, notation) in [
+        (
+            r#""{~u0}{~u1b}{~u7f}{~x80}{~u9f}{~t}{{{"}""#,
+            Notation::braces(),
+        ),
+        (
+            r#""{~u0}{~u1B}{~u7F}{~x80}{~u9F}{~t}{{{"}""#,
+            Notation::braces().uppercase(true),
+        ),
+        (r#""\u{0}\u{1b}\u{7f}\x80\u{9f}\t{{{"}""#, Notation::rust()),
+        (
+            r#""<U+0000><U+001B><U+007F><0x80><U+009F><U+0009>{{{"}""#,
+            Notation::unicode(),
+        ),
+        (
+            r#""^@^[^?{~x80}{~u9f}^I{{{"}""#,
+            Notation::braces().caret(true),
+        ),
+    ] {
+        test(expected, bytes.quote().notation(notation));
+    }
+
+    test(
+        r#""^@^@{~xa0*2}""#,
+        b"\0\0\xA0\xA0"
+            .quote()
+            .compact(true)
+            .notation(Notation::braces().caret(true)),
+    );
+    test(
+        r#""\x80\x80""#,
+        b"\x80\x80".quote().compact(true).notation(Notation::rust()),
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_quoting_styles() {
+    use uniquote::QuotingStyle;
+
+    const STYLES: [QuotingStyle; 7] = [
+        QuotingStyle::Literal,
+        QuotingStyle::Shell,
+        QuotingStyle::ShellAlways,
+        QuotingStyle::ShellEscape,
+        QuotingStyle::ShellEscapeAlways,
+        QuotingStyle::C,
+        QuotingStyle::Escape,
+    ];
+
+    // These values were output by
+    // "LC_ALL=C.UTF-8 ls -dq --quoting-style=<style>" (GNU coreutils 9.1).
+    for (name, expected) in [
+        (
+            &b"plain.txt"[..],
+            [
+                "plain.txt",
+                "plain.txt",
+                "'plain.txt'",
+                "plain.txt",
+                "'plain.txt'",
+                r#""plain.txt""#,
+                "plain.txt",
+            ],
+        ),
+        (
+            &b"a b"[..],
+            [
+                "a b", "'a b'", "'a b'", "'a b'", "'a b'", r#""a b""#,
+                r#"a\ b"#,
+            ],
+        ),
+        (
+            &b"it's"[..],
+            [
+                "it's",
+                r#""it's""#,
+                r#""it's""#,
+                r#""it's""#,
+                r#""it's""#,
+                r#""it's""#,
+                "it's",
+            ],
+        ),
+        (
+            &b"it's here"[..],
+            [
+                "it's here",
+                r#""it's here""#,
+                r#""it's here""#,
+                r#""it's here""#,
+                r#""it's here""#,
+                r#""it's here""#,
+                r#"it's\ here"#,
+            ],
+        ),
+        (
+            &b"a\nb"[..],
+            [
+                "a?b",
+                "'a?b'",
+                "'a?b'",
+                r#"'a'$'\n''b'"#,
+                r#"'a'$'\n''b'"#,
+                r#""a\nb""#,
+                r#"a\nb"#,
+            ],
+        ),
+        (
+            &b"tab\there"[..],
+            [
+                "tab?here",
+                "'tab?here'",
+                "'tab?here'",
+                r#"'tab'$'\t''here'"#,
+                r#"'tab'$'\t''here'"#,
+                r#""tab\there""#,
+                r#"tab\there"#,
+            ],
+        ),
+        (
+            &b"x\x01y"[..],
+            [
+                "x?y",
+                "x?y",
+                "'x?y'",
+                r#"'x'$'\001''y'"#,
+                r#"'x'$'\001''y'"#,
+                r#""x\001y""#,
+                r#"x\001y"#,
+            ],
+        ),
+        (
+            &b"caf\xC3\xA9"[..],
+            [
+                "café",
+                "café",
+                "'café'",
+                "café",
+                "'café'",
+                r#""café""#,
+                "café",
+            ],
+        ),
+        (
+            &b"caf\xE9"[..],
+            [
+                "caf?",
+                "caf?",
+                "'caf?'",
+                r#"'caf'$'\351'"#,
+                r#"'caf'$'\351'"#,
+                r#""caf\351""#,
+                r#"caf\351"#,
+            ],
+        ),
+        (
+            &b"q?"[..],
+            ["q?", "'q?'", "'q?'", "'q?'", "'q?'", r#""q?""#, "q?"],
+        ),
+        (
+            &b"back\\slash"[..],
+            [
+                r#"back\slash"#,
+                r#"'back\slash'"#,
+                r#"'back\slash'"#,
+                r#"'back\slash'"#,
+                r#"'back\slash'"#,
+                r#""back\\slash""#,
+                r#"back\\slash"#,
+            ],
+        ),
+        (
+            &b"dq\"x"[..],
+            [
+                r#"dq"x"#,
+                r#"'dq"x'"#,
+                r#"'dq"x'"#,
+                r#"'dq"x'"#,
+                r#"'dq"x'"#,
+                r#""dq\"x""#,
+                r#"dq"x"#,
+            ],
+        ),
+        (
+            &b"$HOME"[..],
+            [
+                "$HOME",
+                "'$HOME'",
+                "'$HOME'",
+                "'$HOME'",
+                "'$HOME'",
+                r#""$HOME""#,
+                "$HOME",
+            ],
+        ),
+        (
+            &b"#hash"[..],
+            [
+                "#hash",
+                "'#hash'",
+                "'#hash'",
+                "'#hash'",
+                "'#hash'",
+                r##""#hash""##,
+                "#hash",
+            ],
+        ),
+        (
+            &b"mid#hash"[..],
+            [
+                "mid#hash",
+                "mid#hash",
+                "'mid#hash'",
+                "mid#hash",
+                "'mid#hash'",
+                r#""mid#hash""#,
+                "mid#hash",
+            ],
+        ),
+        (
+            &b"~tilde"[..],
+            [
+                "~tilde",
+                "'~tilde'",
+                "'~tilde'",
+                "'~tilde'",
+                "'~tilde'",
+                r#""~tilde""#,
+                "~tilde",
+            ],
+        ),
+        (&b"{"[..], ["{", "'{'", "'{'", "'{'", "'{'", r#""{""#, "{"]),
+        (
+            &b"{}"[..],
+            ["{}", "{}", "'{}'", "{}", "'{}'", r#""{}""#, "{}"],
+        ),
+        (
+            &b"semi;colon"[..],
+            [
+                "semi;colon",
+                "'semi;colon'",
+                "'semi;colon'",
+                "'semi;colon'",
+                "'semi;colon'",
+                r#""semi;colon""#,
+                "semi;colon",
+            ],
+        ),
+        (
+            &b"new\nline's"[..],
+            [
+                "new?line's",
+                r#"'new?line'\''s'"#,
+                r#"'new?line'\''s'"#,
+                r#"'new'$'\n''line'\''s'"#,
+                r#"'new'$'\n''line'\''s'"#,
+                r#""new\nline's""#,
+                r#"new\nline's"#,
+            ],
+        ),
+        (
+            &b"bad\xE2\x82b"[..],
+            [
+                "bad??b",
+                "bad??b",
+                "'bad??b'",
+                r#"'bad'$'\342\202''b'"#,
+                r#"'bad'$'\342\202''b'"#,
+                r#""bad\342\202b""#,
+                r#"bad\342\202b"#,
+            ],
+        ),
+        (
+            &b"end\xE2\x82"[..],
+            [
+                "end?",
+                "end?",
+                "'end??'",
+                r#"'end'$'\342\202'"#,
+                r#"'end'$'\342\202'"#,
+                r#""end\342\202""#,
+                r#"end\342\202"#,
+            ],
+        ),
+        (
+            &b"@at"[..],
+            ["@at", "@at", "'@at'", "@at", "'@at'", r#""@at""#, "@at"],
+        ),
+        (
+            &b"nul\x7Fdel"[..],
+            [
+                "nul?del",
+                "nul?del",
+                "'nul?del'",
+                r#"'nul'$'\177''del'"#,
+                r#"'nul'$'\177''del'"#,
+                r#""nul\177del""#,
+                r#"nul\177del"#,
+            ],
+        ),
+        (
+            &b"!bang"[..],
+            [
+                "!bang",
+                "'!bang'",
+                "'!bang'",
+                "'!bang'",
+                "'!bang'",
+                r#""!bang""#,
+                "!bang",
+            ],
+        ),
+        (
+            &b"a\rb"[..],
+            [
+                "a?b",
+                "'a?b'",
+                "'a?b'",
+                r#"'a'$'\r''b'"#,
+                r#"'a'$'\r''b'"#,
+                r#""a\rb""#,
+                r#"a\rb"#,
+            ],
+        ),
+        (
+            &b"oct\x017"[..],
+            [
+                "oct?7",
+                "oct?7",
+                "'oct?7'",
+                r#"'oct'$'\001''7'"#,
+                r#"'oct'$'\001''7'"#,
+                r#""oct\0017""#,
+                r#"oct\0017"#,
+            ],
+        ),
+        (
+            &b"-dash"[..],
+            [
+                "-dash",
+                "-dash",
+                "'-dash'",
+                "-dash",
+                "'-dash'",
+                r#""-dash""#,
+                "-dash",
+            ],
+        ),
+        (&b""[..], ["", "''", "''", "''", "''", r#""""#, ""]),
+    ] {
+        for (style, expected) in STYLES.into_iter().zip(expected) {
+            test(expected, name.quote().quoting_style(style));
+        }
+    }
+
+    struct Utf16<'a>(&'a [u16]);
+
+    impl Quote for Utf16<'_> {
+        fn escape(&self, f: &mut uniquote::Formatter<'_>) -> uniquote::Result {
+            f.escape_utf16(self.0.iter().copied())
+        }
+    }
+
+    // Surrogates cannot be represented as bytes, so the default format is
+    // used.
+    test(
+        r#""a{~ud800}b""#,
+        Utf16(&[0x61, 0xD800, 0x62])
+            .quote()
+            .quoting_style(QuotingStyle::Shell),
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_git() {
+    use uniquote::QuotingStyle;
+
+    // These values were output by "git status --porcelain" and
+    // "git ls-files" (Git 2.39).
+    for (path, expected, expected_status) in [
+        (&b"plain.txt"[..], "plain.txt", "plain.txt"),
+        (b"a b", "a b", r#""a b""#),
+        (b"caf\xC3\xA9", r#""caf\303\251""#, r#""caf\303\251""#),
+        (b"caf\xE9", r#""caf\351""#, r#""caf\351""#),
+        (b"tab\there", r#""tab\there""#, r#""tab\there""#),
+        (b"new\nline", r#""new\nline""#, r#""new\nline""#),
+        (b"dq\"x", r#""dq\"x""#, r#""dq\"x""#),
+        (b"back\\slash", r#""back\\slash""#, r#""back\\slash""#),
+        (b"del\x7F", r#""del\177""#, r#""del\177""#),
+        (b"bell\x07", r#""bell\a""#, r#""bell\a""#),
+        (b"x\x01y", r#""x\001y""#, r#""x\001y""#),
+        (b"it's", "it's", "it's"),
+        (b"{~x80}", "{~x80}", "{~x80}"),
+    ] {
+        test(expected, path.quote().quoting_style(QuotingStyle::Git));
+        test(
+            expected_status,
+            path.quote().quoting_style(QuotingStyle::GitStatus),
+        );
+        for quoted in [expected, expected_status] {
+            assert_eq!(Ok(path), uniquote::unquote_git(quoted).as_deref());
+        }
+    }
+
+    for (offset, quoted) in [
+        (4, r#""abc"#),
+        (4, r#""ab"c"#),
+        (2, r#""\q""#),
+        (2, r#""\48""#),
+        (3, r#""\38""#),
+        (4, r#""\30"#),
+    ] {
+        let error = uniquote::unquote_git(quoted).unwrap_err();
+        assert_eq!(offset, error.offset());
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_debug() {
+    use uniquote::DebugString;
+
+    for string in [
+        "",
+        "plain",
+        "tab\tnull\0",
+        "quotes\"'\\",
+        "\u{1b}\u{7f}\u{feff}\u{10ffff}",
+        "caf\u{e9}",
+    ] {
+        let quoted = format!("{:?}", string);
+        assert_eq!(
+            Ok(string.as_bytes()),
+            uniquote::unquote_debug(&quoted).as_deref(),
+        );
+        let parsed: DebugString = quoted.parse().unwrap();
+        test(&string.quote().to_string(), parsed.quote());
+    }
+
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let bytes = b"fo\x80o\xC3\xA9\xFF\n";
+        let quoted = format!("{:?}", OsStr::from_bytes(bytes));
+        assert_eq!(
+            Ok(&bytes[..]),
+            uniquote::unquote_debug(&quoted).as_deref(),
+        );
+        #[cfg(feature = "os_str_bytes")]
+        assert_eq!(
+            Ok(OsStr::from_bytes(bytes)),
+            uniquote::unquote_debug_os_string(&quoted).as_deref(),
+        );
+    }
+
+    let parsed: DebugString = r#""a\u{d800}b""#.parse().unwrap();
+    test(r#""a{~ud800}b""#, parsed.quote());
+
+    for (offset, quoted) in [
+        (0, "abc"),
+        (4, r#""abc"#),
+        (4, r#""ab"c"#),
+        (2, r#""\q""#),
+        (4, r#""\x8g""#),
+        (3, r#""\u[""#),
+        (10, r#""\u{1234567}""#),
+        (1, r#""\u{110000}""#),
+        (1, r#""\u{d800}""#),
+        (3, r#""\x"#),
+    ] {
+        let error = uniquote::unquote_debug(quoted).unwrap_err();
+        assert_eq!(offset, error.offset(), "{}", quoted);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Bytes(#[serde(with = "uniquote::serde::bytes")] Vec<u8>);
+
+    for (bytes, expected) in [
+        (&b""[..], r#""\"\"""#),
+        (b"\xA0\xFF{\"}\n", r#""\"{~xa0}{~xff}{{{\"}}}{~n}\"""#),
+        (b"caf\xC3\xA9", r#""\"café\"""#),
+    ] {
+        let bytes = Bytes(bytes.to_vec());
+        let json = serde_json::to_string(&bytes).unwrap();
+        assert_eq!(expected, json);
+        assert_eq!(bytes, serde_json::from_str(&json).unwrap());
+    }
+
+    for json in [r#""\"{~q}\"""#, r#""abc""#, "1"] {
+        assert!(serde_json::from_str::<Bytes>(json).is_err());
+    }
+
+    #[cfg(all(feature = "os_str_bytes", unix))]
+    {
+        use std::ffi::OsStr;
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::PathBuf;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Paths {
+            #[serde(with = "uniquote::serde::os_string")]
+            name: OsString,
+            #[serde(with = "uniquote::serde::path_buf")]
+            path: PathBuf,
+        }
+
+        let name = OsStr::from_bytes(b"\xA0\x80");
+        let paths = Paths {
+            name: name.to_owned(),
+            path: PathBuf::from(name).join("file\t"),
+        };
+        let json = serde_json::to_string(&paths).unwrap();
+        assert_eq!(
+            r#"{"name":"\"{~xa0}{~x80}\"","path":"\"{~xa0}{~x80}/file{~t}\""}"#,
+            json,
+        );
+        assert_eq!(paths, serde_json::from_str(&json).unwrap());
+    }
+
+    #[cfg(all(feature = "os_str_bytes", windows))]
+    {
+        use std::ffi::OsString;
+        use std::os::windows::ffi::OsStringExt;
+        use std::path::PathBuf;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Paths {
+            #[serde(with = "uniquote::serde::os_string")]
+            name: OsString,
+            #[serde(with = "uniquote::serde::path_buf")]
+            path: PathBuf,
+        }
+
+        let name = OsString::from_wide(&[0x61, 0xD800]);
+        let paths = Paths {
+            path: PathBuf::from(&name),
+            name,
+        };
+        let json = serde_json::to_string(&paths).unwrap();
+        assert_eq!(r#"{"name":"\"a{~ud800}\"","path":"\"a{~ud800}\""}"#, json);
+        assert_eq!(paths, serde_json::from_str(&json).unwrap());
+    }
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive() {
+    #[derive(Quote)]
+    struct Unit;
+
+    #[derive(Quote)]
+    struct Newtype<'a>(&'a str);
+
+    #[derive(Quote)]
+    #[quote(separator = ", ")]
+    struct Named<'a, T: ?Sized> {
+        name: &'a T,
+        value: char,
+    }
+
+    #[derive(Quote)]
+    struct Generic<'a, T: ?Sized>(&'a T);
+
+    #[derive(Quote)]
+    #[quote(separator = "=")]
+    enum Enum<'a> {
+        Unit,
+        Tuple(&'a &'a str, &'a [u8]),
+        #[quote(separator = "{")]
+        Named {
+            first: char,
+            second: Newtype<'a>,
+        },
+    }
+
+    #[derive(Quote)]
+    enum Empty {}
+
+    // A separator is not written for a single field.
+    #[derive(Quote)]
+    #[quote(separator = ",")]
+    struct Mixed(Unit);
+
+    fn assert_quote<T>()
+    where
+        T: Quote,
+    {
+    }
+
+    assert_quote::<Empty>();
+
+    test(r#""""#, Unit.quote());
+    test(r#""a{~t}b""#, Newtype("a\tb").quote());
+    test(
+        r#""a{~n}, {"}""#,
+        Named {
+            name: "a\n",
+            value: '"',
+        }
+        .quote(),
+    );
+    test(r#""{~x80}""#, Generic(b"\x80").quote());
+    test(r#""""#, Enum::Unit.quote());
+    test(r#""a={~x80}""#, Enum::Tuple(&"a", b"\x80").quote());
+    test(
+        r#""x{{{~n}""#,
+        Enum::Named {
+            first: 'x',
+            second: Newtype("\n"),
+        }
+        .quote(),
+    );
+    test(r#""""#, Mixed(Unit).quote());
+}
+
+#[cfg(all(feature = "macros", feature = "std"))]
+#[test]
+fn test_macros() {
+    use uniquote::qformat;
+
+    let tab = "a\tb";
+    let bytes = b"\x80";
+    let width = 6;
+
+    assert_eq!("", qformat!(""));
+    assert_eq!("{} {~t}", qformat!("{{}} {{~t}}"));
+    assert_eq!(r#""a{~t}b""#, qformat!("{:q}", tab));
+    assert_eq!(
+        r#""a{~t}b" 1 "{~x80}""#,
+        qformat!("{:q} {} {:q}", tab, 1, bytes)
+    );
+    assert_eq!(r#"2 "a{~t}b" 1"#, qformat!("{2} {0:q} {1}", tab, 1, 2));
+    assert_eq!(r#""{~x80}" 1.50"#, qformat!("{bytes:q} {:.*}", 2, 1.5));
+    assert_eq!(
+        "  1.50 \"a{~t}b\"",
+        qformat!("{:>1$.2} {tab:q}", 1.5, width)
+    );
+    assert_eq!("1.5   |", qformat!("{:<width$}|", 1.5));
+    assert_eq!(
+        r#"a	b "a{~t}b" "a{~t}b""#,
+        qformat!("{x} {x:q} {0:q}", x = tab),
+    );
+    assert_eq!(r#""a{~t}b" 1"#, qformat!("{x:q} {}", 1, x = tab,));
+    assert_eq!(r#""{"}""#, qformat!("{:q}", String::from("\"")));
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_write_macros() {
+    use std::fmt::Write;
+
+    let tab = "a\tb";
+
+    let mut buffer = String::new();
+    uniquote::qwrite!(buffer, "{tab:q}").unwrap();
+    uniquote::qwriteln!(buffer, " {:q}", 'x').unwrap();
+    uniquote::qwriteln!(buffer).unwrap();
+    assert_eq!("\"a{~t}b\" \"x\"\n\n", buffer);
+}
+
+#[cfg(feature = "os_str_bytes")]
+#[test]
+fn test_parse_quoted_arg() {
+    use std::ffi::OsStr;
+
+    for (expected, arg) in [
+        ("", ""),
+        ("\"", "\""),
+        ("", r#""""#),
+        ("file", "file"),
+        ("\"file", "\"file"),
+        ("{~n}", "{~n}"),
+        ("\n", r#""{~n}""#),
+        ("\"file\"", r#""{"}file{"}""#),
+    ] {
+        assert_eq!(
+            Ok(OsStr::new(expected)),
+            uniquote::parse_quoted_arg(OsStr::new(arg)).as_deref(),
+        );
+    }
+
+    for (offset, arg) in [(3, r#""{~q}""#), (3, r#""a"b""#)] {
+        let error = uniquote::parse_quoted_arg(OsStr::new(arg)).unwrap_err();
+        assert_eq!(offset, error.offset());
+    }
+
+    #[cfg(windows)]
+    {
+        use std::ffi::OsString;
+        use std::os::windows::ffi::OsStringExt;
+
+        let arg = OsString::from_wide(&[0x61, 0xD800, 0xD83D, 0xDE00]);
+        let quoted = arg.quote().to_string();
+        assert_eq!("\"a{~ud800}\u{1F600}\"", quoted);
+        assert_eq!(Ok(arg), uniquote::parse_quoted_arg(OsStr::new(&quoted)),);
+    }
+    #[cfg(not(windows))]
+    assert!(uniquote::parse_quoted_arg(OsStr::new(r#""a{~ud800}""#)).is_err(),);
+
+    #[cfg(feature = "clap")]
+    {
+        use std::ffi::OsString;
+
+        use clap::error::ErrorKind;
+        use clap::Arg;
+        use clap::Command;
+        use uniquote::QuotedArgParser;
+
+        let command = Command::new("app")
+            .arg(Arg::new("file").value_parser(QuotedArgParser::new()));
+        let matches = command
+            .clone()
+            .try_get_matches_from(["app", r#""a{~t}b""#])
+            .unwrap();
+        assert_eq!(
+            Some(&OsString::from("a\tb")),
+            matches.get_one::<OsString>("file"),
+        );
+
+        let error = command
+            .try_get_matches_from(["app", r#""{~q}""#])
+            .unwrap_err();
+        assert_eq!(ErrorKind::ValueValidation, error.kind());
+        assert!(error.to_string().contains(r#""{"}{{~q}}{"}""#));
+    }
+}
+
+#[cfg(feature = "os_str_bytes")]
+#[test]
+fn test_path_error() {
+    use std::error::Error;
+    use std::io;
+    use std::path::Path;
+
+    use uniquote::IoResultExt;
+    use uniquote::PathError;
+
+    let result: io::Result<()> =
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+    let error = result.with_path("dir/{file}\t").unwrap_err();
+    test(r#"I/O error for path "dir/{{file}}{~t}""#, &error);
+    assert_eq!(Path::new("dir/{file}\t"), error.path());
+    assert_eq!(io::ErrorKind::PermissionDenied, error.kind());
+    test("denied", error.source().unwrap());
+    test("denied", error.io_error());
+
+    let error = io::Error::from(error);
+    assert_eq!(io::ErrorKind::PermissionDenied, error.kind());
+    let error = error.into_inner().unwrap().downcast::<PathError>().unwrap();
+    let (path, error) = error.into_parts();
+    assert_eq!(Path::new("dir/{file}\t"), path);
+    test("denied", error);
+
+    assert_eq!(Ok(1), Ok::<_, io::Error>(1).with_path("file").map_err(drop));
+
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let error = PathError::new(
+            OsStr::from_bytes(b"\xFF"),
+            io::Error::new(io::ErrorKind::NotFound, "missing"),
+        );
+        test(r#"I/O error for path "{~xff}""#, error);
+    }
+}
+
+#[cfg(feature = "os_str_bytes")]
+#[test]
+fn test_quoted_command() {
+    use std::process::Command;
+
+    use uniquote::QuotedCommand;
+    use uniquote::QuotingStyle;
+
+    let mut command = Command::new("echo");
+    test(r#""echo""#, QuotedCommand::new(&command));
+
+    let _ = command.arg("it's").arg("").env("B", "1=2").env_remove("A");
+    test(
+        r#"env -u "A" "B"="1=2" "echo" "it's" """#,
+        QuotedCommand::new(&command),
+    );
+    test(
+        r#"env -u A B='1=2' echo "it's" ''"#,
+        QuotedCommand::new(&command).quoting_style(QuotingStyle::Shell),
+    );
+
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut command = Command::new("cat");
+        let _ = command
+            .arg(OsStr::from_bytes(b"\xFF\xA0"))
+            .current_dir(OsStr::from_bytes(b"\x80"));
+        test(
+            r#"cd "{~x80}" && "cat" "{~xff}{~xa0}""#,
+            QuotedCommand::new(&command),
+        );
+        test(
+            r"cd ''$'\200' && 'cat' ''$'\377\240'",
+            QuotedCommand::new(&command)
+                .quoting_style(QuotingStyle::ShellEscapeAlways),
+        );
+    }
+}
+
+#[cfg(feature = "os_str_bytes")]
+#[test]
+fn test_quoted_args() {
+    use std::ffi::OsString;
+
+    use uniquote::QuotedArgs;
+    use uniquote::QuotedEnv;
+    use uniquote::QuotingStyle;
+
+    let args = ["a", "", "b c", "\"d\""].map(OsString::from);
+    let quoted = QuotedArgs::new(&args);
+    test(r#""a" "" "b c" "{"}d{"}""#, quoted);
+    test(
+        r#"a '' 'b c' '"d"'"#,
+        quoted.quoting_style(QuotingStyle::Shell),
+    );
+    test("", QuotedArgs::new(&[] as &[OsString]));
+
+    let vars = [("A=B", "=\n"), ("", "x")];
+    test(r#""A=B"="={~n}""#, QuotedEnv::new(&vars[..1]));
+    test("\"A=B\"=\"={~n}\"\n\"\"=\"x\"", QuotedEnv::new(&vars));
+    test(
+        "'A=B'='='$'\\n'\n''=x",
+        QuotedEnv::new(&vars).quoting_style(QuotingStyle::ShellEscape),
+    );
+    test("", QuotedEnv::new(&[] as &[(OsString, OsString)]));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use std::fmt::Debug;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use tracing::field::Field;
+    use tracing::field::Visit;
+    use tracing::span;
+    use tracing::Event;
+    use tracing::Metadata;
+    use tracing::Subscriber;
+
+    use uniquote::field;
+
+    struct Visitor<'a>(&'a mut Vec<String>);
+
+    impl Visit for Visitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            span.record(&mut Visitor(&mut self.0.lock().unwrap()));
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+            values.record(&mut Visitor(&mut self.0.lock().unwrap()));
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut Visitor(&mut self.0.lock().unwrap()));
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    test(r#""a{~n}b""#, format!("{:?}", field::quoted("a\nb")));
+    test(r#""{~x80}""#, format!("{}", field::quoted(b"\x80")));
+
+    let fields = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = Recorder(Arc::clone(&fields));
+    tracing::subscriber::with_default(subscriber, || {
+        let _span = tracing::info_span!("span", path = field::quoted("a\nb"))
+            .entered();
+        tracing::info!(path = field::quoted(b"\x80"));
+    });
+    assert_eq!(
+        *fields.lock().unwrap(),
+        [r#"path="a{~n}b""#, r#"path="{~x80}""#],
+    );
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_log() {
+    use log::kv::ToValue;
+
+    test(r#""a{~n}b""#, "a\nb".quote().to_value());
+    test(r#""{~x80}""#, b"\x80".quote().to_value());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_binary() {
+    use uniquote::Binary;
+
+    let bytes = (0..=u8::MAX).collect::<Vec<_>>();
+    let hex_dump = bytes.quote().binary(Binary::hex_dump()).to_string();
+    let mut lines = hex_dump.lines();
+    assert_eq!(Some("<binary data: 256 bytes, hex dump>"), lines.next());
+    assert_eq!(
+        Some(concat!(
+            "00000070  70 71 72 73 74 75 76 77  78 79 7a 7b 7c 7d 7e 7f",
+            "  |pqrstuvwxyz{|}~.|",
+        )),
+        lines.nth(7),
+    );
+    assert_eq!(17, hex_dump.lines().count());
+
+    let string = "a\u{200B}".repeat(20);
+    test(
+        "<binary data: 80 bytes, FNV-1a hash 0x2835c1fcffb3e19d>",
+        string.quote().binary(Binary::summary()),
+    );
+    let quoted = string.quote().to_string();
+    test(
+        &quoted,
+        string.quote().binary(Binary::summary().threshold(50)),
+    );
+    test(
+        &quoted,
+        string.quote().binary(Binary::summary().min_len(41)),
+    );
+
+    // Surrogates cannot be decoded.
+    struct Surrogates;
+
+    impl Quote for Surrogates {
+        fn escape(&self, f: &mut uniquote::Formatter<'_>) -> uniquote::Result {
+            f.escape_utf32([0xD800; 32])
+        }
+    }
+
+    assert!(Surrogates
+        .quote()
+        .binary(Binary::summary())
+        .to_string()
+        .starts_with(r#""{~ud800}"#));
+}
+
+#[cfg(feature = "color")]
+#[test]
+fn test_colors() {
+    use uniquote::Colors;
+
+    let colors = Colors::new().escape("7").quote("2");
+    test(
+        "\x1B[2m\"\x1B[0ma\x1B[7m{~u1b}\x1B[0m[0m\x1B[2m\"\x1B[0m",
+        "a\x1B[0m".quote().colors(colors),
+    );
+    test(r#""a{~u1b}""#, "a\x1B".quote().colors(Colors::NONE));
+}
+
+#[test]
+fn test_formatter() {
+    use uniquote::Formatter;
+
+    struct Parts;
+
+    impl Quote for Parts {
+        fn escape(&self, f: &mut Formatter<'_>) -> uniquote::Result {
+            f.escape_bytes(b"a\x80")?;
+            f.escape_char('\n')?;
+            f.escape_utf32([0x62, 0xDFFF, 0x200B, u32::MAX])?;
+            f.write_literal_unchecked("{~x}")
+        }
+    }
+
+    test(
+        r#""a{~x80}{~n}b{~udfff}{~u200b}{~uffffffff}{~x}""#,
+        Parts.quote(),
+    );
+}
+
+#[test]
+fn test_iterators() {
+    use uniquote::Formatter;
+
+    struct Bytes<'a>(&'a [u8]);
+
+    impl Quote for Bytes<'_> {
+        fn escape(&self, f: &mut Formatter<'_>) -> uniquote::Result {
+            f.escape_byte_iter(self.0.iter().copied())
+        }
+    }
+
+    struct Chars<'a>(&'a str);
+
+    impl Quote for Chars<'_> {
+        fn escape(&self, f: &mut Formatter<'_>) -> uniquote::Result {
+            f.escape_chars(self.0.chars())
+        }
+    }
+
+    fn test_bytes(bytes: &[u8]) {
+        test(&bytes.quote().to_string(), Bytes(bytes).quote());
+    }
+
+    for x in 0..=u8::MAX {
+        for y in 0..=u8::MAX {
+            test_bytes(&[x, y]);
+            test_bytes(&[b'a', x, y, b'b']);
+        }
+    }
+    for bytes in [
+        &b"\xE2\x82\xAC\xE2\x82A"[..],
+        b"\xF0\x9F\x98\x80\xF0\x9F\x98",
+        b"\xED\xA0\x80\xED\x9F\xBF",
+        b"\xF4\x90\x80\x80\xF4\x8F\xBF\xBF",
+        b"\xE0\x80\x80\xE0\xA0\x80",
+    ] {
+        test_bytes(bytes);
+    }
+
+    let string = "a\u{200B}{\"\té";
+    test(&string.quote().to_string(), Chars(string).quote());
+}
+
+#[test]
+fn test_stream_escaper() {
+    use uniquote::StreamEscaper;
+
+    let bytes = "a\u{10FFFF}b\u{20AC}\n\u{E9}"
+        .bytes()
+        .chain(*b"\xF0\x9F\x98\xE2\x82\xAC\xF4")
+        .collect::<Vec<_>>();
+    let expected = bytes.quote().to_string();
+    for size in 1..=bytes.len() {
+        let mut escaper = StreamEscaper::new(String::from('"'));
+        for chunk in bytes.chunks(size) {
+            escaper.feed(chunk).unwrap();
+        }
+        let mut result = escaper.finish().unwrap();
+        result.push('"');
+        assert_eq!(expected, result);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_quoting_writer() {
+    use std::io::Write;
+
+    use uniquote::QuotingWriter;
+
+    let bytes = b"\xF0\x9F\x98\x80\n\x1B\xC3";
+
+    let mut writer = QuotingWriter::new(Vec::new());
+    for &byte in bytes {
+        writer.write_all(&[byte]).unwrap();
+    }
+    assert_eq!(
+        "\u{1F600}{~n}{~u1b}Ã",
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    );
+
+    let mut writer = QuotingWriter::new(Vec::new()).literal_newlines(true);
+    writer.write_all(bytes).unwrap();
+    assert_eq!(b"\xF0\x9F\x98\x80\n{~u1b}", &**writer.get_ref());
+
+    let mut writer = QuotingWriter::new(Vec::new()).exact(true);
+    writer.write_all(bytes).unwrap();
+    assert_eq!(
+        b"\xF0\x9F\x98\x80{~n}{~u1b}{~xc3}",
+        &*writer.finish().unwrap(),
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_unquoting_reader() {
+    use std::io::Read;
+    use std::io::Write;
+
+    use uniquote::QuotingWriter;
+    use uniquote::UnquotingReader;
+
+    fn read(reader: UnquotingReader<&[u8]>) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let _ = reader.take(u64::MAX).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    let bytes = (0..=u8::MAX).chain(*b"{\"}\n\xF0\x9F").collect::<Vec<_>>();
+    for literal_newlines in [false, true] {
+        let mut writer = QuotingWriter::new(Vec::new())
+            .exact(true)
+            .literal_newlines(literal_newlines);
+        writer.write_all(&bytes).unwrap();
+        let quoted = writer.finish().unwrap();
+        assert_eq!(bytes, read(UnquotingReader::new(&quoted)).unwrap());
+    }
+
+    let lines = [&b"fo\x80o\t"[..], b"", "{\u{200B}}\"".as_bytes()];
+    let quoted: String =
+        lines.iter().map(|x| format!("{}\n", x.quote())).collect();
+    let mut expected = lines.join(&b'\n');
+    expected.push(b'\n');
+    assert_eq!(
+        expected,
+        read(UnquotingReader::new(quoted.as_bytes()).lines(true)).unwrap(),
+    );
+
+    for (line, offset, quoted) in [
+        (1, 3, &br#"ab}c"#[..]),
+        (2, 6, b"ab\n{~u}"),
+        (1, 7, b"{~ud800}"),
+        (1, 5, b"{~x80"),
+    ] {
+        let error = read(UnquotingReader::new(quoted)).unwrap_err();
+        let error = error
+            .into_inner()
+            .unwrap()
+            .downcast::<uniquote::ParseError>()
+            .unwrap();
+        assert_eq!((line, offset), (error.line(), error.offset()));
+    }
+    let error =
+        read(UnquotingReader::new(&b"\"a\"\nb"[..]).lines(true)).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+}